@@ -14,35 +14,8 @@ fn main() {
         .with(Child::new("vendor").with_size(60).optional_with_priority(9))
         .build()
         .unwrap();
-    let sum_sizes = container.sizes().iter().sum::<usize>();
-    assert!(sum_sizes <= terminal_width);
+    assert!(container.used() <= terminal_width);
 
     println!("terminal_width: {}", terminal_width);
-    // name row
-    let mut added = false;
-    for child in &container.children {
-        if let Some(size) = child.size() {
-            if added {
-                print!("|");
-            } else {
-                added = true;
-            }
-            let name = child.content();
-            print!("{name:^size$}");
-        }
-    }
-    println!();
-    // size row
-    let mut added = false;
-    for child in &container.children {
-        if let Some(size) = child.size() {
-            if added {
-                print!("|");
-            } else {
-                added = true;
-            }
-            print!("{size:^size$}");
-        }
-    }
-    println!();
+    println!("{}", container.debug_table());
 }
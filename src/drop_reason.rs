@@ -0,0 +1,11 @@
+/// The reason why a child wasn't included in the container
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DropReason {
+    /// The child would have fit, but the space was taken by
+    /// optional children of higher priority
+    LowerPriority,
+    /// The child doesn't fit, even in the space left after
+    /// the required children
+    NoSpace,
+}
@@ -0,0 +1,8 @@
+/// A side of a child, in the direction of the layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    /// Towards the children added before
+    Start,
+    /// Towards the children added after
+    End,
+}
@@ -0,0 +1,49 @@
+use crate::*;
+
+/// Two independent layouts, one for the rows and one for the columns,
+/// composed into a grid of cells.
+///
+/// ```
+/// use flex_grow::{Child, Container, Grid};
+///
+/// let rows = Container::builder_in(10)
+///     .with(Child::new("header").with_size(1))
+///     .with(Child::new("body").with_min(3));
+/// let cols = Container::builder_in(32)
+///     .with_margin_between(1)
+///     .with(Child::new("a").with_size(10))
+///     .with(Child::new("b").with_min(5))
+///     .with(Child::new("c").with_size(6));
+/// let grid = Grid::new(rows, cols).unwrap();
+/// assert_eq!(grid.cell_rect(1, 1), Some((11, 1, 14, 9)));
+/// assert_eq!(grid.cell_rect(0, 2), Some((26, 0, 6, 1)));
+/// assert_eq!(grid.cell_rect(2, 0), None);
+/// ```
+pub struct Grid<R, C> {
+    rows: Container<R>,
+    cols: Container<C>,
+}
+
+impl<R, C> Grid<R, C> {
+    pub fn new(rows: ContainerBuilder<R>, cols: ContainerBuilder<C>) -> Result<Self, Error> {
+        Ok(Grid {
+            rows: rows.build()?,
+            cols: cols.build()?,
+        })
+    }
+    pub fn rows(&self) -> &Container<R> {
+        &self.rows
+    }
+    pub fn cols(&self) -> &Container<C> {
+        &self.cols
+    }
+    /// Return the `(x, y, width, height)` of a cell, or none if the row
+    /// or the column isn't included (or doesn't exist)
+    pub fn cell_rect(&self, row: usize, col: usize) -> Option<(usize, usize, usize, usize)> {
+        let h = self.rows.children.get(row)?.size()?;
+        let w = self.cols.children.get(col)?.size()?;
+        let y = self.rows.offsets()[row];
+        let x = self.cols.offsets()[col];
+        Some((x, y, w, h))
+    }
+}
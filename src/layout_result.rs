@@ -0,0 +1,13 @@
+use alloc::vec::Vec;
+
+/// The outcome of a layout, without the contents of the children,
+/// so that it can be stored or sent elsewhere
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LayoutResult {
+    pub available: usize,
+    pub margin: usize,
+    /// The sizes of the children, none for non-included ones
+    pub sizes: Vec<Option<usize>>,
+    pub offsets: Vec<usize>,
+}
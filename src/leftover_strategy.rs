@@ -0,0 +1,15 @@
+/// How the cells left by the rounding of the shares are given
+/// to the growable children
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum LeftoverStrategy {
+    /// One cell to each child in turn, the first children being served
+    /// first
+    #[default]
+    FirstFit,
+    /// One cell to each child in turn, the children with the biggest
+    /// grow factor being served first
+    ByGrow,
+    /// Spread evenly among the children, instead of going to the first ones
+    RoundRobin,
+}
@@ -25,36 +25,134 @@
 //!
 //! You can give any argument to `Child::new`, it's stored in the child and returned by the `content()` method.
 //!
+//! The crate is `no_std` (it only needs `alloc`) when built without the default `std` feature.
+//!
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+mod drop_reason;
+mod edge;
+mod grid;
+mod layout_result;
+mod leftover_strategy;
+mod optional_strategy;
+mod rounding;
 
-use std::fmt;
+pub use {
+    drop_reason::*, edge::*, grid::*, layout_result::*, leftover_strategy::*, optional_strategy::*,
+    rounding::*,
+};
 
+use {
+    alloc::{
+        boxed::Box,
+        format,
+        string::{String, ToString},
+        sync::Arc,
+        vec,
+        vec::Vec,
+    },
+    core::{borrow::Borrow, fmt},
+};
+
+#[derive(Clone)]
 pub struct ContainerBuilder<C> {
     available: usize,
-    margin_between: usize,
+    settings: Settings,
     children: Vec<Child<C>>,
+    leftover_preference: Option<LeftoverPreference<C>>,
 }
 
+/// The configuration of a container, kept so that it can be laid out again
 #[derive(Debug, Clone, Copy, Default)]
+struct Settings {
+    margin_between: usize,
+    margin_around: usize,
+    trailing_fill: bool,
+    auto_expand: bool,
+    fair_floor: f64,
+    optional_strategy: OptionalStrategy,
+    leftover_strategy: LeftoverStrategy,
+    size_multiple: usize,
+    inter_group_margin: Option<usize>,
+    presorted_optionals: bool,
+    priority_grouping: bool,
+    pinned_edges: bool,
+    margin_between_percent: Option<f64>,
+    scale: Option<f64>,
+    reject_zero: bool,
+    min_growth: usize,
+    max_total: Option<usize>,
+    no_stretch: bool,
+    reversed: bool,
+    all_grow: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Optionality {
     #[default]
     Required,
     Optional {
         priority: usize, // bigger is more important
     },
+    /// Always included, but may shrink down to `floor` to leave space
+    /// to optional children of higher priority
+    Squeezable { priority: usize, floor: usize },
 }
 
+#[derive(Clone)]
 pub struct Child<C> {
     content: C,
     constraints: ChildConstraints,
+    // the constraints used by the last layout, resolved from the
+    // available size and the settings
+    resolved: ChildConstraints,
+    min_of_available: Option<MinOfAvailable>,
     size: Option<usize>, // None if not (yet) included
+    drop_reason: Option<DropReason>,
+    effective_grow: f64,
+    // the position at which the last layout considered the child
+    rank: Option<usize>,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChildConstraints {
     pub min: usize,
     pub max: Option<usize>,
     pub optionality: Optionality,
     pub grow: f64,
+    pub shrink: f64,
+    /// The size under which the child never shrinks
+    pub shrink_floor: usize,
+    /// The fraction of the available size the min is computed from
+    pub fraction: Option<f64>,
+    /// Whether the child is drawn over the other ones, taking no space
+    pub overlay: bool,
+    /// Whether the child takes all the remaining space, the other
+    /// children not growing
+    pub fill: bool,
+    /// The size the child grows to before the others grow further
+    pub target: Option<usize>,
+    pub reserve_when_dropped: bool,
+    /// The value of the child, used by `OptionalStrategy::ValueDensity`
+    pub value: f64,
+    /// The group of the child, used with an inter group margin
+    pub group: Option<usize>,
+    /// The margin between the child and the next included one, replacing
+    /// the margin between children of the container
+    pub margin_after: Option<usize>,
+    /// The category of the child, which must keep at least one included child
+    pub category: Option<usize>,
+    /// The bundle of the child, whose optional children are included
+    /// together or not at all
+    pub bundle: Option<usize>,
+    pub rounding: Rounding,
+    /// The size of the child is rounded down to a multiple of the step
+    pub step: usize,
 }
 
 impl Default for ChildConstraints {
@@ -64,39 +162,772 @@ impl Default for ChildConstraints {
             max: None,
             optionality: Optionality::default(),
             grow: 1.0,
+            shrink: 0.0,
+            shrink_floor: 0,
+            fraction: None,
+            overlay: false,
+            fill: false,
+            target: None,
+            reserve_when_dropped: false,
+            value: 1.0,
+            group: None,
+            margin_after: None,
+            category: None,
+            bundle: None,
+            rounding: Rounding::default(),
+            step: 1,
         }
     }
 }
 
+/// A function computing the min size of a child from the available size
+type MinOfAvailable = Arc<dyn Fn(usize) -> usize + Send + Sync>;
+
+/// A function scoring the children, the ones with the biggest scores
+/// receiving the leftover cells first
+type LeftoverPreferenceFn<C> = dyn Fn(&Child<C>) -> f64 + Send + Sync;
+type LeftoverPreference<C> = Arc<LeftoverPreferenceFn<C>>;
+
+/// A callback receiving the sizes of the children after a reflow
+type ReflowCallback = Box<dyn FnMut(&[usize]) + Send + Sync>;
+
 pub struct Container<C> {
     pub children: Vec<Child<C>>,
+    available: usize,
+    settings: Settings,
+    leftover_preference: Option<LeftoverPreference<C>>,
+    on_reflow: Option<ReflowCallback>,
 }
 
 #[derive(Debug, Clone)]
 pub enum Error {
-    NotEnoughSpace,
+    /// The required children don't fit: `needed` is the size taken by
+    /// the required children (with the margins) up to the one at
+    /// `child_index`, the first one which doesn't fit
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container, Error};
+    ///
+    /// let result = Container::builder_in(30)
+    ///     .with_margin_between(2)
+    ///     .with(Child::new("a").with_min(10))
+    ///     .with(Child::new("b").with_size(20).optional())
+    ///     .with(Child::new("c").with_min(12))
+    ///     .with(Child::new("d").with_min(18))
+    ///     .build();
+    /// let Err(e) = result else {
+    ///     panic!("the required children shouldn't fit");
+    /// };
+    /// assert!(matches!(
+    ///     e,
+    ///     Error::NotEnoughSpace { needed: 44, available: 30, child_index: 3 },
+    /// ));
+    /// assert_eq!(
+    ///     e.to_string(),
+    ///     "Not enough space: need 44 cells but only 30 available, failed at child 3",
+    /// );
+    /// ```
+    NotEnoughSpace {
+        needed: usize,
+        available: usize,
+        child_index: usize,
+    },
+    /// The constraints are too big for the sizes to be computed
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container, Error};
+    ///
+    /// let result = Container::builder_in(usize::MAX)
+    ///     .with(Child::new("a").with_min(usize::MAX / 2 + 1))
+    ///     .with(Child::new("b").with_min(usize::MAX / 2 + 1))
+    ///     .build();
+    /// assert!(matches!(result, Err(Error::Overflow)));
+    ///
+    /// // huge margins around an optional child don't overflow either
+    /// let container = Container::builder_in(usize::MAX)
+    ///     .with_margin_between(usize::MAX / 2 + 1)
+    ///     .with(Child::new("a"))
+    ///     .with(Child::new("b").optional())
+    ///     .with(Child::new("c"))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.children()[1].size(), None);
+    /// ```
+    Overflow,
+    /// There are children but the available size is zero, which is an
+    /// error only if the container was built with `with_reject_zero(true)`
+    ZeroAvailable,
+    /// A child has a max smaller than its min
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container, Error};
+    ///
+    /// let result = Container::builder_in(20)
+    ///     .with(Child::new("x").with_min(10).with_max(3))
+    ///     .build();
+    /// assert!(matches!(result, Err(Error::InvalidConstraints { min: 10, max: 3 })));
+    /// ```
+    InvalidConstraints { min: usize, max: usize },
+    /// The size of the child at `index` doesn't respect its constraints,
+    /// which may only happen after a manual change of the sizes
+    SizeOutOfBounds { index: usize, size: usize },
 }
+#[cfg(feature = "std")]
 impl std::error::Error for Error {}
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Error::NotEnoughSpace => write!(f, "Not enough space"),
+            Error::NotEnoughSpace {
+                needed,
+                available,
+                child_index,
+            } => write!(
+                f,
+                "Not enough space: need {} cells but only {} available, failed at child {}",
+                needed, available, child_index
+            ),
+            Error::Overflow => write!(f, "Overflow in size computation"),
+            Error::ZeroAvailable => write!(f, "Zero available size"),
+            Error::SizeOutOfBounds { index, size } => {
+                write!(f, "Size {} of child {} is out of its bounds", size, index)
+            }
+            Error::InvalidConstraints { min, max } => {
+                write!(
+                    f,
+                    "Invalid constraints: max {} is smaller than min {}",
+                    max, min
+                )
+            }
         }
     }
 }
 
 impl ChildConstraints {}
 
+/// Return the indices of the children in the order the allocation
+/// considers them: required children first, in declaration order,
+/// then the other ones in the order of the strategy (or in declaration
+/// order if they're declared presorted), the most important optional child
+/// of each category without an always included child coming first
+fn priority_order<C>(children: &[Child<C>], settings: &Settings) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..children.len()).collect();
+    if settings.presorted_optionals {
+        let (required, others): (Vec<usize>, Vec<usize>) =
+            order.into_iter().partition(|&i| children[i].priority().is_none());
+        order = required;
+        order.extend(others);
+    } else {
+        sort_by_strategy(children, &mut order, settings);
+    }
+    let mut represented: Vec<usize> = children
+        .iter()
+        .filter(|c| c.required_size().is_some())
+        .filter_map(|c| c.resolved.category)
+        .collect();
+    let mut protected = vec![false; children.len()];
+    for &i in &order {
+        if let Some(category) = children[i].resolved.category {
+            if !represented.contains(&category) {
+                represented.push(category);
+                protected[i] = true;
+            }
+        }
+    }
+    // stable, so the order is otherwise kept
+    order.sort_by_key(|&i| (children[i].priority().is_some(), !protected[i]));
+    order
+}
+
+/// Sort the indices of the children: required children first, in
+/// declaration order, then the other ones by decreasing value density
+/// (with `OptionalStrategy::ValueDensity`) and priority, ties being broken
+/// by declaration order (reversed in a reversed container)
+fn sort_by_strategy<C>(children: &[Child<C>], order: &mut [usize], settings: &Settings) {
+    use core::cmp::Ordering;
+    order.sort_by(|&a, &b| {
+        let (ca, cb) = (&children[a], &children[b]);
+        match (ca.priority(), cb.priority()) {
+            (None, None) => a.cmp(&b),
+            (None, Some(_)) => Ordering::Less,
+            (Some(_), None) => Ordering::Greater,
+            (Some(pa), Some(pb)) => {
+                let by_density = if settings.optional_strategy == OptionalStrategy::ValueDensity {
+                    cb.value_density()
+                        .partial_cmp(&ca.value_density())
+                        .unwrap_or(Ordering::Equal)
+                } else {
+                    Ordering::Equal
+                };
+                let by_declaration = if settings.reversed {
+                    b.cmp(&a)
+                } else {
+                    a.cmp(&b)
+                };
+                by_density.then(pb.cmp(&pa)).then(by_declaration)
+            }
+        }
+    });
+}
+
+/// Return the number of cells to remove from each required child so that
+/// they fit despite missing `deficit` cells, proportionally to their
+/// shrink factors and to how much they can shrink before reaching their
+/// shrink floor, or none if that's not possible
+fn shrinkage_to_fit<C>(children: &[Child<C>], deficit: usize) -> Option<Vec<usize>> {
+    let room: Vec<usize> = children
+        .iter()
+        .map(|c| match c.required_size() {
+            Some(size) if c.resolved.shrink > 0.0 => size.saturating_sub(c.resolved.shrink_floor),
+            _ => 0,
+        })
+        .collect();
+    if room.iter().sum::<usize>() < deficit {
+        return None;
+    }
+    let weights: Vec<f64> = children
+        .iter()
+        .zip(&room)
+        .map(|(c, &room)| c.resolved.shrink * room as f64)
+        .collect();
+    let sum_weights: f64 = weights.iter().sum();
+    let mut shrinkage: Vec<usize> = weights
+        .iter()
+        .zip(&room)
+        .map(|(w, &room)| ((deficit as f64 * w / sum_weights) as usize).min(room))
+        .collect();
+    // the cells missing due to down rounding are taken one by one
+    let mut missing = deficit - shrinkage.iter().sum::<usize>();
+    while missing > 0 {
+        for (shrunk, &room) in shrinkage.iter_mut().zip(&room) {
+            if *shrunk < room && missing > 0 {
+                *shrunk += 1;
+                missing -= 1;
+            }
+        }
+    }
+    Some(shrinkage)
+}
+
+/// Return the greatest common divisor of two numbers
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Round a size to the nearest integer, half away from zero
+/// (`f64::round` isn't available without std)
+fn round(size: f64) -> usize {
+    let truncated = size as usize;
+    if size - truncated as f64 >= 0.5 {
+        truncated.saturating_add(1)
+    } else {
+        truncated
+    }
+}
+
+/// Round a size up to the next integer
+/// (`f64::ceil` isn't available without std)
+fn ceil(size: f64) -> usize {
+    let truncated = size as usize;
+    if (truncated as f64) < size {
+        truncated.saturating_add(1)
+    } else {
+        truncated
+    }
+}
+
 impl<C> ContainerBuilder<C> {
     pub fn with_available(available: usize) -> Self {
         ContainerBuilder {
             available,
             children: Vec::new(),
-            margin_between: 0,
+            settings: Settings::default(),
+            leftover_preference: None,
         }
     }
     pub fn with_margin_between(mut self, margin: usize) -> Self {
-        self.margin_between = margin;
+        self.settings.margin_between = margin;
+        self.settings.margin_between_percent = None;
+        self
+    }
+    /// Set the margin between children as a percentage of the available
+    /// size, rounded down, the remaining cells going to the children
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(41)
+    ///     .with_margin_between_percent(7.5)
+    ///     .with(Child::new("a"))
+    ///     .with(Child::new("b"))
+    ///     .with(Child::new("c"))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![12, 12, 11]);
+    /// assert_eq!(container.offsets(), vec![0, 15, 30]);
+    /// assert_eq!(container.used(), 41);
+    /// ```
+    pub fn with_margin_between_percent(mut self, percent: f64) -> Self {
+        self.settings.margin_between_percent = Some(percent);
+        self
+    }
+    /// Reserve `margin` cells before the first child and after the last
+    /// one, independently of the margin between children.
+    ///
+    /// Those cells are reserved even when no child is included.
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let builder = || {
+    ///     Container::builder_in(30)
+    ///         .with_margin_between(1)
+    ///         .with(Child::new("a").with_size(10))
+    ///         .with(Child::new("b"))
+    /// };
+    /// let container = builder().build().unwrap();
+    /// assert_eq!(container.sizes(), vec![10, 19]);
+    /// assert_eq!(container.offsets(), vec![0, 11]);
+    /// let container = builder().with_margin_around(2).build().unwrap();
+    /// assert_eq!(container.sizes(), vec![10, 15]);
+    /// assert_eq!(container.offsets(), vec![2, 13]);
+    /// assert_eq!(container.used(), 30);
+    /// ```
+    pub fn with_margin_around(mut self, margin: usize) -> Self {
+        self.settings.margin_around = margin;
+        self
+    }
+    /// Make the built container report, with `trailing_fill()`, the
+    /// region after the last included child which isn't used by the children
+    pub fn with_trailing_fill(mut self, trailing_fill: bool) -> Self {
+        self.settings.trailing_fill = trailing_fill;
+        self
+    }
+    /// When `auto_expand` is true and the required children don't fit,
+    /// make the container as wide as needed instead of failing
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let builder = Container::builder_in(20)
+    ///     .with_margin_between(1)
+    ///     .with(Child::new("a").with_size(10))
+    ///     .with(Child::new("b").with_min(15))
+    ///     .with(Child::new("c").with_size(3).optional());
+    /// let container = builder.with_auto_expand(true).build().unwrap();
+    /// assert_eq!(container.available(), 26);
+    /// assert_eq!(container.sizes(), vec![10, 15, 0]);
+    /// ```
+    pub fn with_auto_expand(mut self, auto_expand: bool) -> Self {
+        self.settings.auto_expand = auto_expand;
+        self
+    }
+    /// Guarantee that every growable child gets at least the given fraction
+    /// (from 0.0 to 1.0) of its proportional share of the remaining space,
+    /// rounded up, taking cells from the children with the biggest growths
+    /// if needed
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let builder = || {
+    ///     Container::builder_in(10)
+    ///         .with(Child::new("a").with_grow(10.0))
+    ///         .with(Child::new("b").with_grow(10.0))
+    ///         .with(Child::new("c"))
+    /// };
+    /// assert_eq!(builder().build().unwrap().sizes(), vec![5, 5, 0]);
+    /// assert_eq!(
+    ///     builder().with_fair_floor(1.0).build().unwrap().sizes(),
+    ///     vec![5, 4, 1],
+    /// );
+    /// ```
+    pub fn with_fair_floor(mut self, fair_floor: f64) -> Self {
+        self.settings.fair_floor = fair_floor;
+        self
+    }
+    /// Guarantee that every growable child which hasn't reached its max
+    /// grows by at least `cells` (when there's enough space for all),
+    /// taking cells from the children with the biggest growths if needed
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let builder = || {
+    ///     Container::builder_in(26)
+    ///         .with(Child::new("a").with_min(5).with_grow(10.0))
+    ///         .with(Child::new("b").with_min(5).with_grow(10.0))
+    ///         .with(Child::new("c").with_min(5))
+    ///         .with(Child::new("d").with_min(5))
+    /// };
+    /// assert_eq!(builder().build().unwrap().sizes(), vec![8, 8, 5, 5]);
+    /// let container = builder().with_min_growth(1).build().unwrap();
+    /// assert_eq!(container.sizes(), vec![7, 7, 6, 6]);
+    /// ```
+    pub fn with_min_growth(mut self, cells: usize) -> Self {
+        self.settings.min_growth = cells;
+        self
+    }
+    /// Set the order in which the optional children are included
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container, OptionalStrategy};
+    ///
+    /// let builder = || {
+    ///     Container::builder_in(20)
+    ///         .with(Child::new("a").with_size(4))
+    ///         .with(Child::new("b").with_size(12).optional_with_value(10.0))
+    ///         .with(Child::new("c").with_size(6).optional_with_value(6.0))
+    ///         .with(Child::new("d").with_size(6).optional_with_value(6.0))
+    /// };
+    /// let container = builder()
+    ///     .with_optional_strategy(OptionalStrategy::Priority)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![4, 12, 0, 0]);
+    /// let container = builder()
+    ///     .with_optional_strategy(OptionalStrategy::ValueDensity)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![4, 0, 6, 6]);
+    /// ```
+    pub fn with_optional_strategy(mut self, strategy: OptionalStrategy) -> Self {
+        self.settings.optional_strategy = strategy;
+        self
+    }
+    /// Set how the cells left by the rounding of the shares are given
+    /// to the growable children
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container, LeftoverStrategy};
+    ///
+    /// let builder = || {
+    ///     Container::builder_in(21)
+    ///         .with(Child::new("a"))
+    ///         .with(Child::new("b"))
+    ///         .with(Child::new("c").with_grow(2.0))
+    ///         .with(Child::new("d"))
+    ///         .with(Child::new("e"))
+    ///         .with(Child::new("f").with_grow(2.0))
+    /// };
+    /// let sizes = |strategy| {
+    ///     builder()
+    ///         .with_leftover_strategy(strategy)
+    ///         .build()
+    ///         .unwrap()
+    ///         .sizes()
+    /// };
+    /// // the shares are 2.625 and 5.25 cells, leaving 3 cells to distribute
+    /// assert_eq!(sizes(LeftoverStrategy::FirstFit), vec![3, 3, 6, 2, 2, 5]);
+    /// assert_eq!(sizes(LeftoverStrategy::ByGrow), vec![3, 2, 6, 2, 2, 6]);
+    /// assert_eq!(sizes(LeftoverStrategy::RoundRobin), vec![2, 3, 5, 3, 2, 6]);
+    /// ```
+    pub fn with_leftover_strategy(mut self, strategy: LeftoverStrategy) -> Self {
+        self.settings.leftover_strategy = strategy;
+        self
+    }
+    /// Make the sizes of the included children multiples of `multiple`,
+    /// by rounding them down then giving the saved cells, `multiple`
+    /// at a time, to the children which can grow.
+    ///
+    /// A child whose min isn't a multiple may end smaller than its min
+    /// (its resolved min is then lowered to its size).
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(31)
+    ///     .with_margin_between(1)
+    ///     .with_size_multiple(2)
+    ///     .with(Child::new("a").with_min(4))
+    ///     .with(Child::new("b").with_min(6))
+    ///     .with(Child::new("c").with_min(4))
+    ///     .build()
+    ///     .unwrap();
+    /// let sizes = container.sizes();
+    /// assert_eq!(sizes, vec![10, 10, 8]);
+    /// assert!(sizes.iter().all(|size| size % 2 == 0));
+    /// assert!(sizes.iter().sum::<usize>() + 2 <= 31);
+    /// ```
+    pub fn with_size_multiple(mut self, multiple: usize) -> Self {
+        self.settings.size_multiple = multiple;
+        self
+    }
+    /// Use `margin` between consecutive included children which aren't
+    /// in the same group, the margin between children of a same group
+    /// being the one set with `with_margin_between`
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(40)
+    ///     .with_inter_group_margin(2)
+    ///     .with(Child::new("a").with_size(5).in_group(1))
+    ///     .with(Child::new("b").with_size(5).in_group(1))
+    ///     .with(Child::new("c").with_size(5).in_group(2))
+    ///     .with(Child::new("d").with_size(5).in_group(2))
+    ///     .with(Child::new("e").with_size(50).optional().in_group(2))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![5, 5, 5, 5, 0]);
+    /// assert_eq!(container.offsets(), vec![0, 5, 12, 17, 22]);
+    /// ```
+    pub fn with_inter_group_margin(mut self, margin: usize) -> Self {
+        self.settings.inter_group_margin = Some(margin);
+        self
+    }
+    /// Promise that the optional and squeezable children are declared in
+    /// the order they must be considered (by decreasing priority), so that
+    /// they don't have to be sorted.
+    ///
+    /// If the promise isn't kept, the layout is wrong (but still valid).
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let builder = || {
+    ///     Container::builder_in(30)
+    ///         .with_margin_between(1)
+    ///         .with(Child::new("a").with_min(10))
+    ///         .with(Child::new("b").with_size(8).optional_with_priority(9))
+    ///         .with(Child::new("c").with_size(8).optional_with_priority(5))
+    ///         .with(Child::new("d").with_size(8).optional_with_priority(2))
+    /// };
+    /// let sorted = builder().build().unwrap();
+    /// let presorted = builder().with_presorted_optionals(true).build().unwrap();
+    /// assert_eq!(presorted.sizes(), vec![12, 8, 8, 0]);
+    /// assert_eq!(presorted.sizes(), sorted.sizes());
+    /// ```
+    pub fn with_presorted_optionals(mut self, presorted: bool) -> Self {
+        self.settings.presorted_optionals = presorted;
+        self
+    }
+    /// Include the optional children of a same priority all together,
+    /// or not at all
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let builder = || {
+    ///     Container::builder_in(30)
+    ///         .with(Child::new("a").with_size(10))
+    ///         .with(Child::new("b").with_size(8).optional_with_priority(5))
+    ///         .with(Child::new("c").with_size(8).optional_with_priority(5))
+    ///         .with(Child::new("d").with_size(8).optional_with_priority(5))
+    ///         .with(Child::new("e").with_size(4).optional_with_priority(2))
+    /// };
+    /// let container = builder().build().unwrap();
+    /// assert_eq!(container.sizes(), vec![10, 8, 8, 0, 4]);
+    /// let container = builder().with_priority_grouping(true).build().unwrap();
+    /// assert_eq!(container.sizes(), vec![10, 0, 0, 0, 4]);
+    /// ```
+    pub fn with_priority_grouping(mut self, grouping: bool) -> Self {
+        self.settings.priority_grouping = grouping;
+        self
+    }
+    /// Set a function scoring the children, so that the cells left after
+    /// the rounding of the shares go first to the children with the
+    /// biggest scores (instead of going in declaration order)
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let builder = || {
+    ///     Container::builder_in(21)
+    ///         .with(Child::new("short"))
+    ///         .with(Child::new("a much longer content"))
+    /// };
+    /// let container = builder().build().unwrap();
+    /// assert_eq!(container.sizes(), vec![11, 10]);
+    /// let container = builder()
+    ///     .with_leftover_preference(|child| {
+    ///         // how truncated the content is
+    ///         child.content().len() as f64 - child.size().unwrap_or(0) as f64
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![10, 11]);
+    /// ```
+    pub fn with_leftover_preference<F>(mut self, preference: F) -> Self
+    where
+        F: Fn(&Child<C>) -> f64 + Send + Sync + 'static,
+    {
+        self.leftover_preference = Some(Arc::new(preference));
+        self
+    }
+    /// Make the first and the last children required and fixed at their
+    /// min, so that only the children between them grow
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container, Optionality};
+    ///
+    /// let container = Container::builder_in(40)
+    ///     .with_margin_between(1)
+    ///     .with_pinned_edges(true)
+    ///     .with(Child::new("label").with_min(6))
+    ///     .with(Child::new("value").with_min(10))
+    ///     .with(Child::new("action").with_min(8).optional())
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![6, 24, 8]);
+    ///
+    /// // the declared constraints are kept, only the resolved ones change
+    /// let action = &container.children()[2];
+    /// assert_eq!(action.constraints().max, None);
+    /// assert_eq!(action.constraints().optionality, Optionality::Optional { priority: 0 });
+    /// assert_eq!(action.resolved_max(), Some(8));
+    /// ```
+    pub fn with_pinned_edges(mut self, pinned: bool) -> Self {
+        self.settings.pinned_edges = pinned;
+        self
+    }
+    /// Multiply the mins and maxes of all children by `scale` (rounding
+    /// to the nearest integer) before laying them out, for a zoom
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let builder = |scale| {
+    ///     Container::builder_in(30)
+    ///         .with_scale(scale)
+    ///         .with(Child::new("a").with_size(10))
+    ///         .with(Child::new("b").clamp(4, 10))
+    ///         .with(Child::new("c").with_size(6).optional())
+    /// };
+    /// assert_eq!(builder(1.0).build().unwrap().sizes(), vec![10, 10, 6]);
+    /// assert_eq!(builder(0.5).build().unwrap().sizes(), vec![5, 5, 3]);
+    /// assert_eq!(builder(2.0).build().unwrap().sizes(), vec![20, 10, 0]);
+    /// assert!(builder(3.0).build().is_err());
+    ///
+    /// // the scaled bounds are the resolved ones
+    /// let container = builder(0.5).build().unwrap();
+    /// assert_eq!(container.children()[0].resolved_min(), 5);
+    /// assert_eq!(container.children()[0].resolved_max(), Some(5));
+    /// assert_eq!(container.children()[1].grew_by(), 3);
+    /// ```
+    pub fn with_scale(mut self, scale: f64) -> Self {
+        self.settings.scale = Some(scale);
+        self
+    }
+    /// Lay out the children in at most `max` cells, even when more are
+    /// available, the rest being left unused (see `Container::leftover`)
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(300)
+    ///     .with_margin_between(1)
+    ///     .with_max_total(100)
+    ///     .with(Child::new("a").with_min(10))
+    ///     .with(Child::new("b").with_min(20).with_grow(2.0))
+    ///     .with(Child::new("c").with_size(30).optional())
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![23, 45, 30]);
+    /// assert_eq!(container.used(), 100);
+    /// assert_eq!(container.available(), 300);
+    /// assert_eq!(container.leftover(), 200);
+    /// ```
+    pub fn with_max_total(mut self, max: usize) -> Self {
+        self.settings.max_total = Some(max);
+        self
+    }
+    /// Leave the children at the sizes chosen when including them (their
+    /// mins, usually), without any growth, the rest of the available
+    /// size being left unused.
+    ///
+    /// The sizes are still rounded to the steps and to the size multiple.
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(40)
+    ///     .with_margin_between(1)
+    ///     .with_no_stretch(true)
+    ///     .with(Child::new("a").with_min(10))
+    ///     .with(Child::new("b").with_min(5).optional())
+    ///     .with(Child::new("c").with_min(8).fill())
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![10, 5, 8]);
+    /// assert_eq!(container.leftover(), 15);
+    ///
+    /// let container = Container::builder_in(40)
+    ///     .with_no_stretch(true)
+    ///     .with_size_multiple(2)
+    ///     .with(Child::new("a").with_min(5))
+    ///     .with(Child::new("b").with_min(7).with_step(3))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![6, 6]);
+    /// ```
+    pub fn with_no_stretch(mut self, no_stretch: bool) -> Self {
+        self.settings.no_stretch = no_stretch;
+        self
+    }
+    /// Make the layout fail with `Error::ZeroAvailable` when the available
+    /// size is zero and there are children, as it's probably a bug
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container, Error};
+    ///
+    /// let builder = |available| Container::builder_in(available).with_reject_zero(true);
+    /// let result = builder(0).with(Child::new("a")).build();
+    /// assert!(matches!(result, Err(Error::ZeroAvailable)));
+    /// assert!(builder(0).build().is_ok());
+    /// assert!(builder(1).with(Child::new("a")).build().is_ok());
+    /// let container = Container::builder_in(0).with(Child::new("a")).build();
+    /// assert_eq!(container.unwrap().sizes(), vec![0]);
+    /// ```
+    pub fn with_reject_zero(mut self, reject: bool) -> Self {
+        self.settings.reject_zero = reject;
+        self
+    }
+    /// Override the grow factor of all children, whatever they set
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(30)
+    ///     .set_all_grow(2.0)
+    ///     .with(Child::new("a").with_grow(5.0))
+    ///     .with(Child::new("b"))
+    ///     .with(Child::new("c").with_grow(0.0))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![10, 10, 10]);
+    /// // the declared grow factors are kept
+    /// assert_eq!(container.children()[0].constraints().grow, 5.0);
+    /// ```
+    pub fn set_all_grow(mut self, grow: f64) -> Self {
+        self.settings.all_grow = Some(grow);
+        self
+    }
+    /// Favor the last children instead of the first ones, as is needed
+    /// for a right-to-left layout: they get the cells left by the rounding
+    /// first, and they're included first among optional children of
+    /// the same priority
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let builder = |reversed| {
+    ///     Container::builder_in(11)
+    ///         .with_reversed(reversed)
+    ///         .with(Child::new("a"))
+    ///         .with(Child::new("b"))
+    ///         .with(Child::new("c"))
+    /// };
+    /// assert_eq!(builder(false).build().unwrap().sizes(), vec![4, 4, 3]);
+    /// let container = builder(true).build().unwrap();
+    /// assert_eq!(container.sizes(), vec![3, 4, 4]);
+    /// assert_eq!(container.sizes_rev(), vec![4, 4, 3]);
+    /// ```
+    pub fn with_reversed(mut self, reversed: bool) -> Self {
+        self.settings.reversed = reversed;
         self
     }
     pub fn with(mut self, child: Child<C>) -> Self {
@@ -106,97 +937,1094 @@ impl<C> ContainerBuilder<C> {
     pub fn add(&mut self, child: Child<C>) {
         self.children.push(child);
     }
+    /// Add all the children, in order
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let chained = Container::builder_in(40)
+    ///     .with_margin_between(1)
+    ///     .with(Child::new("a").with_min(5))
+    ///     .with(Child::new("b").with_size(20).optional())
+    ///     .with(Child::new("c").with_min(8).with_grow(2.0))
+    ///     .build()
+    ///     .unwrap();
+    /// let all = Container::builder_in(40)
+    ///     .with_margin_between(1)
+    ///     .with_all(vec![
+    ///         Child::new("a").with_min(5),
+    ///         Child::new("b").with_size(20).optional(),
+    ///         Child::new("c").with_min(8).with_grow(2.0),
+    ///     ])
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(all.sizes(), chained.sizes());
+    /// ```
+    pub fn with_all<I>(mut self, children: I) -> Self
+    where
+        I: IntoIterator<Item = Child<C>>,
+    {
+        self.add_all(children);
+        self
+    }
+    pub fn add_all<I>(&mut self, children: I)
+    where
+        I: IntoIterator<Item = Child<C>>,
+    {
+        self.children.extend(children);
+    }
+    /// Add the child only if the required children, this one included,
+    /// still fit in the available size (optional children always fit)
+    ///
+    /// On error, the builder is left unchanged.
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let mut builder = Container::builder_in(20).with_margin_between(1);
+    /// builder.try_add(Child::new("a").with_size(10)).unwrap();
+    /// builder.try_add(Child::new("b").with_size(30).optional()).unwrap();
+    /// assert!(builder.try_add(Child::new("c").with_min(10)).is_err());
+    /// builder.try_add(Child::new("d").with_min(9)).unwrap();
+    /// let container = builder.build().unwrap();
+    /// assert_eq!(container.sizes(), vec![10, 0, 9]);
+    ///
+    /// // the checks are the ones of the build
+    /// let mut builder = Container::builder_in(100).with_max_total(50);
+    /// assert!(builder.try_add(Child::new("a").with_size(80)).is_err());
+    /// let mut builder = Container::builder_in(20).with_margin_between_percent(50.0);
+    /// builder.try_add(Child::new("a").with_size(5)).unwrap();
+    /// assert!(builder.try_add(Child::new("b").with_size(6)).is_err());
+    /// let mut builder = Container::builder_in(20);
+    /// builder.try_add(Child::new("a").with_size(15)).unwrap();
+    /// builder.try_add(Child::new("b").with_size(10).with_shrink(1.0)).unwrap();
+    /// assert_eq!(builder.build().unwrap().sizes(), vec![15, 5]);
+    /// ```
+    pub fn try_add(&mut self, child: Child<C>) -> Result<(), Error> {
+        // the layout is tried on copies of the children, so that the
+        // checks are exactly the ones of the build
+        let mut children: Vec<Child<()>> = self
+            .children
+            .iter()
+            .chain(core::iter::once(&child))
+            .map(Child::without_content)
+            .collect();
+        self.settings
+            .resolved(self.available)
+            .layout(self.available, &mut children, None)?;
+        self.children.push(child);
+        Ok(())
+    }
+    /// Return the smallest available size at which the child at the given
+    /// index would be included, or none if there's no such size
+    ///
+    /// The search assumes that a child included at some size is also
+    /// included at any bigger size, which may not be the case when some
+    /// mins or margins grow with the available size.
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let builder = |available| {
+    ///     Container::builder_in(available)
+    ///         .with_margin_between(1)
+    ///         .with(Child::new("name").clamp(5, 10))
+    ///         .with(Child::new("price").with_size(8).optional_with_priority(7))
+    ///         .with(Child::new("quantity").with_size(8).optional())
+    ///         .with(Child::new("total").with_size(8))
+    ///         .with(Child::new("comments").with_min(10).with_grow(2.0))
+    ///         .with(Child::new("vendor").with_size(60).optional_with_priority(9))
+    /// };
+    /// assert_eq!(builder(50).min_width_to_include(5), Some(86));
+    /// assert_eq!(builder(85).build().unwrap().sizes()[5], 0);
+    /// assert_eq!(builder(86).build().unwrap().sizes()[5], 60);
+    /// assert_eq!(builder(50).min_width_to_include(6), None);
+    ///
+    /// // the margins after the children, and the ones proportional
+    /// // to the available size, are taken into account
+    /// let builder = Container::builder_in(10)
+    ///     .with(Child::new("a").with_size(5).with_margin_after(20))
+    ///     .with(Child::new("b").with_size(5).optional());
+    /// assert_eq!(builder.min_width_to_include(1), Some(30));
+    /// let builder = Container::builder_in(10)
+    ///     .with_margin_between_percent(50.0)
+    ///     .with(Child::new("a").with_size(5))
+    ///     .with(Child::new("b").with_size(5).optional());
+    /// assert_eq!(builder.min_width_to_include(1), Some(19));
+    /// ```
+    pub fn min_width_to_include(&self, index: usize) -> Option<usize> {
+        if index >= self.children.len() {
+            return None;
+        }
+        let mut children: Vec<Child<()>> =
+            self.children.iter().map(Child::without_content).collect();
+        let mut included = |available: usize| {
+            self.settings
+                .resolved(available)
+                .layout(available, &mut children, None)
+                .is_ok()
+                && children[index].size.is_some()
+        };
+        // the size at which all children would fit if the margins
+        // were those resolved for the given available size
+        let all_fit = |available: usize| {
+            let settings = self.settings.resolved(available);
+            let around = settings.margin_around.saturating_mul(2);
+            self.children.iter().fold(around, |sum, child| {
+                let c = &child.constraints;
+                let margin = settings
+                    .margin(c, c)
+                    .max(settings.inter_group_margin.unwrap_or(0));
+                sum.saturating_add(c.min).saturating_add(margin)
+            })
+        };
+        // the margins may grow with the available size, so the bound
+        // is doubled until the child is included
+        let mut high = all_fit(0);
+        while !included(high) {
+            if high == usize::MAX {
+                return None;
+            }
+            high = high.saturating_mul(2).max(all_fit(high)).max(1);
+        }
+        let mut low = 0;
+        while low < high {
+            let mid = (low + high) / 2;
+            if included(mid) {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+        Some(high)
+    }
+    /// Build the container without checking that the required children
+    /// fit, which is only correct if you already know they do.
+    ///
+    /// If they don't, the layout is wrong (but there's no panic).
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let builder = || {
+    ///     Container::builder_in(30)
+    ///         .with_margin_between(1)
+    ///         .with(Child::new("a").with_size(10))
+    ///         .with(Child::new("b").with_min(5))
+    ///         .with(Child::new("c").with_size(8).optional())
+    /// };
+    /// let unchecked = builder().build_unchecked();
+    /// assert_eq!(unchecked.sizes(), vec![10, 10, 8]);
+    /// assert_eq!(unchecked.sizes(), builder().build().unwrap().sizes());
+    /// ```
+    pub fn build_unchecked(self) -> Container<C> {
+        let Self {
+            available,
+            settings,
+            mut children,
+            leftover_preference,
+        } = self;
+        let settings = settings.resolved(available);
+        let allocated = settings.allocated(available);
+        settings.resolve_constraints(&mut children, allocated);
+        settings.distribute(
+            allocated.saturating_sub(settings.margin_around.saturating_mul(2)),
+            &mut children,
+            leftover_preference.as_deref(),
+            &[],
+        );
+        Container {
+            children,
+            available,
+            settings,
+            leftover_preference,
+            on_reflow: None,
+        }
+    }
+    /// Lay out the children and return the `(index, size)` of the
+    /// included ones, in the order they were added
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let builder = || {
+    ///     Container::builder_in(30)
+    ///         .with_margin_between(1)
+    ///         .with(Child::new("a").with_size(10))
+    ///         .with(Child::new("b").with_size(30).optional())
+    ///         .with(Child::new("c").with_min(5))
+    /// };
+    /// let assignments: Vec<_> = builder().assignments().unwrap().collect();
+    /// assert_eq!(assignments, vec![(0, 10), (2, 19)]);
+    /// assert_eq!(builder().build().unwrap().sizes(), vec![10, 0, 19]);
+    /// ```
+    pub fn assignments(self) -> Result<impl Iterator<Item = (usize, usize)>, Error> {
+        let container = self.build()?;
+        Ok(container
+            .children
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, child)| child.size.map(|size| (i, size))))
+    }
+    /// Lay out the children, dropping the optional children which don't
+    /// fit, the ones of lowest priority first, so that the only possible
+    /// `NotEnoughSpace` error is when the required children don't fit
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let builder = |width| {
+    ///     Container::builder_in(width)
+    ///         .with_margin_between(1)
+    ///         .with(Child::new("name").with_min(10))
+    ///         .with(Child::new("size").with_size(8).optional_with_priority(9))
+    ///         .with(Child::new("date").with_size(16).optional_with_priority(5))
+    ///         .with(Child::new("owner").with_size(12).optional_with_priority(3))
+    ///         .with(Child::new("comments").with_min(40).optional_with_priority(1))
+    /// };
+    /// let columns = |width| {
+    ///     let container = builder(width).build().unwrap();
+    ///     container.sized().iter().filter(|size| size.is_some()).count()
+    /// };
+    /// assert_eq!(columns(30), 2);
+    /// assert_eq!(columns(120), 5);
+    /// assert!(builder(9).build().is_err());
+    /// ```
+    ///
+    /// In a zero available size, all children are dropped, without error
+    /// (unless the container is built `with_reject_zero(true)`):
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(0)
+    ///     .with(Child::new("a").with_min(5))
+    ///     .with(Child::new("b").with_size(3).optional())
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![0, 0]);
+    /// assert_eq!(container.sized(), vec![None, None]);
+    /// let container = Container::builder_in(0)
+    ///     .with(Child::new("a").with_size(4).optional())
+    ///     .with(Child::new("b").optional())
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![0, 0]);
+    /// assert_eq!(container.sized(), vec![None, None]);
+    /// ```
     pub fn build(self) -> Result<Container<C>, Error> {
+        self.build_inspect().map_err(|(e, _)| e)
+    }
+    /// Build the container or, on failure, return the error with the
+    /// children so that they can be inspected (only the required children
+    /// which fit before the failing one have a size)
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container, Error};
+    ///
+    /// let result = Container::builder_in(10)
+    ///     .with(Child::new("a").with_size(6))
+    ///     .with(Child::new("b").with_size(6))
+    ///     .with(Child::new("c").with_size(2))
+    ///     .build_inspect();
+    /// let Err((Error::NotEnoughSpace { .. }, children)) = result else {
+    ///     panic!("the children shouldn't fit");
+    /// };
+    /// let contents: Vec<_> = children.iter().map(|c| *c.content()).collect();
+    /// assert_eq!(contents, vec!["a", "b", "c"]);
+    /// let sizes: Vec<_> = children.iter().map(|c| c.size()).collect();
+    /// assert_eq!(sizes, vec![Some(6), None, None]);
+    /// ```
+    pub fn build_inspect(self) -> Result<Container<C>, (Error, Vec<Child<C>>)> {
         let Self {
-            mut available,
+            available,
+            settings,
             mut children,
-            margin_between,
+            leftover_preference,
         } = self;
+        let settings = settings.resolved(available);
+        let available =
+            match settings.layout(available, &mut children, leftover_preference.as_deref()) {
+                Ok(available) => available,
+                Err(e) => {
+                    // the required children placed before the failing one
+                    // keep their sizes
+                    let placed = match e {
+                        Error::NotEnoughSpace { child_index, .. } => child_index,
+                        _ => 0,
+                    };
+                    for (i, child) in children.iter_mut().enumerate() {
+                        child.size = if i < placed {
+                            child.required_size()
+                        } else {
+                            None
+                        };
+                    }
+                    return Err((e, children));
+                }
+            };
+        Ok(Container {
+            children,
+            available,
+            settings,
+            leftover_preference,
+            on_reflow: None,
+        })
+    }
+}
 
-        // first pass: we only add the required children. If their min size
-        // is too big, we return an error.
-        let mut added_children = 0;
-        for child in &mut children {
-            child.size = if child.is_optional() {
-                None
-            } else {
-                let margin = if added_children > 0 {
-                    margin_between
-                } else {
-                    0
-                };
-                if child.constraints.min + margin > available {
-                    return Err(Error::NotEnoughSpace);
+impl Settings {
+    /// Return the settings with the margin computed for the available
+    /// size, if it's a percentage.
+    ///
+    /// The margin is rounded down, the cells lost this way being
+    /// distributed to the children like the rest of the space.
+    fn resolved(&self, available: usize) -> Self {
+        let mut settings = *self;
+        if let Some(percent) = self.margin_between_percent {
+            settings.margin_between = (available as f64 * percent / 100.0) as usize;
+        }
+        settings
+    }
+    /// Compute, from the declared constraints of the children, the ones
+    /// used by the layout, which depend on the available size
+    /// and on the settings
+    fn resolve_constraints<C>(&self, children: &mut [Child<C>], available: usize) {
+        for child in children.iter_mut() {
+            child.resolved = child.constraints;
+            if let Some(min_of_available) = &child.min_of_available {
+                child.resolved.min = min_of_available(available);
+            }
+            if let Some(fraction) = child.resolved.fraction {
+                child.resolved.min = round(fraction * available as f64);
+            }
+            if let Some(grow) = self.all_grow {
+                child.resolved.grow = grow;
+            }
+        }
+        // the mins coming from fractions are reduced so that they don't
+        // prevent the required children from fitting
+        let fractional: usize = children
+            .iter()
+            .filter(|c| c.resolved.fraction.is_some())
+            .filter_map(|c| c.required_size())
+            .fold(0, usize::saturating_add);
+        if let Ok(needed) = self.needed(children.iter()) {
+            if needed > available && fractional > 0 {
+                let kept = fractional - (needed - available).min(fractional);
+                for child in children.iter_mut() {
+                    if child.resolved.fraction.is_some() {
+                        let ratio = kept as f64 / fractional as f64;
+                        child.resolved.min = (child.resolved.min as f64 * ratio) as usize;
+                    }
                 }
-                available -= child.constraints.min;
-                available -= margin;
-                added_children += 1;
-                Some(child.constraints.min)
+            }
+        }
+        if self.pinned_edges {
+            let last = children.len().saturating_sub(1);
+            for i in [0, last] {
+                if let Some(child) = children.get_mut(i) {
+                    child.resolved.optionality = Optionality::Required;
+                    child.resolved.max = Some(child.resolved.min);
+                }
+            }
+        }
+        if let Some(scale) = self.scale {
+            let scaled = |size: usize| round(size as f64 * scale);
+            for child in children.iter_mut() {
+                child.resolved.min = scaled(child.resolved.min);
+                child.resolved.max = child.resolved.max.map(scaled);
+            }
+        }
+    }
+    /// Return the size the required children (and squeezable ones at their
+    /// floor) need, margins included
+    fn needed<'c, C: 'c>(
+        &self,
+        children: impl Iterator<Item = &'c Child<C>>,
+    ) -> Result<usize, Error> {
+        let mut sum = 0usize;
+        let mut previous = None;
+        for child in children {
+            let Some(size) = child.required_size() else {
+                continue;
             };
+            if let Some(previous) = previous {
+                sum = sum
+                    .checked_add(self.margin(previous, &child.resolved))
+                    .ok_or(Error::Overflow)?;
+            }
+            sum = sum.checked_add(size).ok_or(Error::Overflow)?;
+            previous = Some(&child.resolved);
         }
-
-        // second pass: we add the optional children until we run out of space,
-        // by priority
-        let mut optional_children = children
-            .iter_mut()
-            .filter(|c| c.is_optional())
-            .collect::<Vec<_>>();
-        optional_children.sort_by_key(|c| {
-            std::cmp::Reverse(match c.constraints.optionality {
-                Optionality::Optional { priority } => priority,
-                _ => 0,
-            })
+        Ok(sum)
+    }
+    /// Return the error telling the required children don't fit, pointing
+    /// at the first one which doesn't
+    fn not_enough_space<'c, C: 'c>(
+        &self,
+        children: impl Iterator<Item = &'c Child<C>>,
+        available: usize,
+    ) -> Error {
+        let mut needed = self.margin_around.saturating_mul(2);
+        let mut previous = None;
+        let mut child_index = 0;
+        for (i, child) in children.enumerate() {
+            let Some(size) = child.required_size() else {
+                continue;
+            };
+            if let Some(previous) = previous {
+                needed = needed.saturating_add(self.margin(previous, &child.resolved));
+            }
+            needed = needed.saturating_add(size);
+            previous = Some(&child.resolved);
+            child_index = i;
+            if needed > available {
+                break;
+            }
+        }
+        Error::NotEnoughSpace {
+            needed,
+            available,
+            child_index,
+        }
+    }
+    /// Return the margin between two consecutive included children
+    fn margin(&self, a: &ChildConstraints, b: &ChildConstraints) -> usize {
+        match self.inter_group_margin {
+            Some(margin) if a.group.is_none() || a.group != b.group => margin,
+            _ => a.margin_after.unwrap_or(self.margin_between),
+        }
+    }
+    /// Return the increase of the total margin when including the child
+    /// at index `i`, given which children are included
+    fn insertion_margin<C>(
+        &self,
+        children: &[Child<C>],
+        i: usize,
+        included: impl Fn(&Child<C>) -> bool,
+    ) -> usize {
+        let c = &children[i].resolved;
+        let previous = children[..i].iter().rev().find(|&c| included(c));
+        let next = children[i + 1..].iter().find(|&c| included(c));
+        match (previous, next) {
+            (Some(p), Some(n)) => {
+                // computed so that there's no overflow in intermediate sums
+                let before = self.margin(&p.resolved, c);
+                let after = self.margin(c, &n.resolved);
+                let replaced = self.margin(&p.resolved, &n.resolved);
+                if after >= replaced {
+                    before.saturating_add(after - replaced)
+                } else {
+                    before.saturating_sub(replaced - after)
+                }
+            }
+            (Some(p), None) => self.margin(&p.resolved, c),
+            (None, Some(n)) => self.margin(c, &n.resolved),
+            (None, None) => 0,
+        }
+    }
+    /// Compute the sizes of the children, returning the available size
+    /// they were laid out in (which may differ from the requested one
+    /// in auto expand mode)
+    ///
+    /// On error, the sizes of the children aren't modified.
+    fn layout<C>(
+        &self,
+        mut available: usize,
+        children: &mut [Child<C>],
+        leftover_preference: Option<&LeftoverPreferenceFn<C>>,
+    ) -> Result<usize, Error> {
+        if self.reject_zero && available == 0 && !children.is_empty() {
+            return Err(Error::ZeroAvailable);
+        }
+        if available == 0 && !self.auto_expand {
+            for child in children.iter_mut() {
+                child.size = None;
+                child.drop_reason = Some(DropReason::NoSpace);
+                child.effective_grow = 0.0;
+                child.rank = None;
+            }
+            return Ok(0);
+        }
+        // the size the children are laid out in, the rest being left unused
+        let mut allocated = self.allocated(available);
+        self.resolve_constraints(children, allocated);
+        let mut shrinkage = Vec::new();
+        let invalid = children.iter().find_map(|c| match c.resolved.max {
+            Some(max) if max < c.resolved.min => Some(Error::InvalidConstraints {
+                min: c.resolved.min,
+                max,
+            }),
+            _ => None,
+        });
+        let needed = match invalid {
+            Some(e) => Err(e),
+            None => self.needed(children.iter()).and_then(|needed| {
+                self.margin_around
+                    .checked_mul(2)
+                    .and_then(|around| needed.checked_add(around))
+                    .ok_or(Error::Overflow)
+            }),
+        };
+        let needed = needed.and_then(|needed| {
+            if needed > allocated && !self.auto_expand {
+                shrinkage = shrinkage_to_fit(children, needed - allocated)
+                    .ok_or_else(|| self.not_enough_space(children.iter(), allocated))?;
+                Ok(allocated)
+            } else {
+                Ok(needed)
+            }
         });
-        for child in optional_children {
-            let margin = if added_children > 0 {
-                margin_between
+        match needed {
+            Ok(needed) => {
+                allocated = allocated.max(needed);
+                available = available.max(allocated);
+            }
+            Err(e) => {
+                return Err(e);
+            }
+        }
+        self.distribute(
+            allocated - 2 * self.margin_around,
+            children,
+            leftover_preference,
+            &shrinkage,
+        );
+        Ok(available)
+    }
+    /// Return the part of the available size the children are laid out in
+    fn allocated(&self, available: usize) -> usize {
+        self.max_total.map_or(available, |max| available.min(max))
+    }
+    /// Return, for each child, whether it's part of the set of optional
+    /// children which fits in `available` with the biggest sum of priorities
+    /// (solved as a knapsack problem, the children of a bundle being taken
+    /// together).
+    ///
+    /// When the problem is too big to be solved in a reasonable time and
+    /// memory, no child is marked, so that the priority order is used.
+    fn best_optional_set<C>(&self, children: &[Child<C>], available: usize) -> Vec<bool> {
+        // the max number of cells of the solving table
+        const MAX_CELLS: usize = 1 << 22;
+        let mut kept = vec![false; children.len()];
+        let has_required = children.iter().any(|c| c.size.is_some());
+        // every included optional child comes with a margin, except the
+        // first one when there's no required child
+        let capacity = if has_required {
+            available
+        } else {
+            available.saturating_add(self.margin_between)
+        };
+        let weight = |i: usize| {
+            // the margin after the child, which is only counted when there's
+            // a next child, is assumed to be needed
+            let c = &children[i].resolved;
+            let after = self.margin(c, c);
+            let margin = if has_required {
+                self.insertion_margin(children, i, |c| c.size.is_some())
+                    .max(after)
             } else {
-                0
+                after
             };
-            if child.constraints.min + margin > available {
+            children[i].resolved.min.saturating_add(margin)
+        };
+        // the items are the optional children, or their bundles
+        let mut items: Vec<Vec<usize>> = Vec::new();
+        for i in 0..children.len() {
+            if children[i].size.is_some() || children[i].priority().is_none() {
                 continue;
             }
-            available -= child.constraints.min;
-            available -= margin;
-            added_children += 1;
-            child.size = Some(child.constraints.min);
+            let bundle = children[i].resolved.bundle;
+            let item = items
+                .iter_mut()
+                .find(|item| bundle.is_some() && children[item[0]].resolved.bundle == bundle);
+            match item {
+                Some(item) => item.push(i),
+                None => items.push(vec![i]),
+            }
+        }
+        let weights: Vec<usize> = items
+            .iter()
+            .map(|item| {
+                item.iter()
+                    .fold(0, |sum: usize, &i| sum.saturating_add(weight(i)))
+            })
+            .collect();
+        let values: Vec<usize> = items
+            .iter()
+            .map(|item| {
+                item.iter().fold(0, |sum: usize, &i| {
+                    sum.saturating_add(children[i].priority().unwrap_or(0))
+                })
+            })
+            .collect();
+        let total_weight = weights
+            .iter()
+            .fold(0, |sum: usize, &w| sum.saturating_add(w));
+        if total_weight <= capacity {
+            for &i in items.iter().flatten() {
+                kept[i] = true;
+            }
+            return kept;
+        }
+        // the weights and the capacity are divided by the gcd of the weights
+        // to make the table smaller
+        let divisor = weights.iter().fold(0, |a, &b| gcd(a, b)).max(1);
+        let weights: Vec<usize> = weights.iter().map(|w| w / divisor).collect();
+        let capacity = capacity / divisor;
+        let cells = capacity
+            .checked_add(1)
+            .and_then(|width| width.checked_mul(items.len()));
+        if cells.map_or(true, |cells| cells > MAX_CELLS) {
+            return kept;
+        }
+        let mut best = vec![0usize; capacity + 1];
+        let mut taken = vec![vec![false; capacity + 1]; items.len()];
+        for (k, (&w, &value)) in weights.iter().zip(&values).enumerate() {
+            for c in (w..=capacity).rev() {
+                let candidate = best[c - w].saturating_add(value);
+                if candidate > best[c] {
+                    best[c] = candidate;
+                    taken[k][c] = true;
+                }
+            }
         }
+        let mut c = capacity;
+        for k in (0..items.len()).rev() {
+            if taken[k][c] {
+                for &i in &items[k] {
+                    kept[i] = true;
+                }
+                c -= weights[k];
+            }
+        }
+        kept
+    }
+    /// Compute the sizes of the children, assuming the required ones fit
+    /// (if they don't, the sizes are wrong but there's no panic)
+    ///
+    /// `shrinkage` holds, for the children which must be shrunk
+    /// below their min, the number of cells to remove.
+    fn distribute<C>(
+        &self,
+        mut available: usize,
+        children: &mut [Child<C>],
+        leftover_preference: Option<&LeftoverPreferenceFn<C>>,
+        shrinkage: &[usize],
+    ) {
+        let total = available;
 
-        // then we distribute the remaining space to the growable children
-        let mut growths = vec![0.0; children.len()];
-        let mut sum_growths = 0.0;
-        for (i, child) in children.iter().enumerate() {
-            let Some(size) = child.size else {
-                continue;
+        // first pass: we only add the required children, the squeezable ones
+        // at their floor, the shrinkable ones shrunk if needed
+        // (we already checked they fit)
+        let mut previous = None;
+        for (i, child) in children.iter_mut().enumerate() {
+            child.drop_reason = None;
+            child.effective_grow = 0.0;
+            child.rank = None;
+            child.size = if let Some(size) = child.required_size() {
+                let size = size - shrinkage.get(i).copied().unwrap_or(0);
+                if let Some(previous) = &previous {
+                    available = available.saturating_sub(self.margin(previous, &child.resolved));
+                }
+                available = available.saturating_sub(size);
+                previous = Some(child.resolved);
+                Some(size)
+            } else {
+                None
             };
-            growths[i] = child.constraints.grow
-                * (match child.constraints.max {
-                    None => available,
-                    Some(max) => max - size,
-                } as f64);
-            sum_growths += growths[i];
         }
-        for i in 0..children.len() {
-            let Some(size) = children[i].size else {
+
+        // second pass: we add the optional children until we run out of space,
+        // by priority, bringing back the squeezable children towards their min
+        // when their priority comes.
+        // With priority grouping, optional children of the same priority
+        // are included together or not at all, and so are the optional
+        // children of a same bundle, when the first of them comes
+        let available_after_required = available;
+        let mut order = priority_order(children, self);
+        if self.optional_strategy == OptionalStrategy::MinDroppedPriority {
+            let kept = self.best_optional_set(children, available);
+            // stable, so the order is otherwise kept
+            order.sort_by_key(|&i| (children[i].priority().is_some(), !kept[i]));
+        }
+        let mut next_rank = 0;
+        let mut p = 0;
+        while p < order.len() {
+            let i = order[p];
+            p += 1;
+            children[i].consider(&mut next_rank);
+            if children[i].priority().is_none() || children[i].drop_reason.is_some() {
                 continue;
-            };
-            let growth = growths[i] as f64 * (available as f64 / sum_growths);
-            available -= growth as usize;
-            children[i].size = Some(size + growth as usize);
+            }
+            if let Some(size) = children[i].size {
+                let child = &mut children[i];
+                let given = child.resolved.min.saturating_sub(size).min(available);
+                available -= given;
+                child.size = Some(size.saturating_add(given));
+                continue;
+            }
+            let mut tier = vec![i];
+            if self.priority_grouping {
+                while p < order.len()
+                    && children[order[p]].required_size().is_none()
+                    && children[order[p]].priority() == children[i].priority()
+                {
+                    tier.push(order[p]);
+                    p += 1;
+                }
+            }
+            if let Some(bundle) = children[i].resolved.bundle {
+                for (j, child) in children.iter().enumerate() {
+                    if !tier.contains(&j)
+                        && child.resolved.bundle == Some(bundle)
+                        && child.required_size().is_none()
+                        && child.priority().is_some()
+                        && child.size.is_none()
+                    {
+                        tier.push(j);
+                    }
+                }
+            }
+            for &j in &tier {
+                children[j].consider(&mut next_rank);
+            }
+            let available_before_tier = available;
+            let mut fits = true;
+            for &j in &tier {
+                let margin = self.insertion_margin(children, j, |c| c.size.is_some());
+                let min = children[j].resolved.min;
+                if min.saturating_add(margin) > available {
+                    fits = false;
+                    break;
+                }
+                available -= min.saturating_add(margin);
+                children[j].size = Some(min);
+            }
+            if fits {
+                continue;
+            }
+            available = available_before_tier;
+            for &j in &tier {
+                children[j].size = None;
+            }
+            for &j in &tier {
+                let margin = self.insertion_margin(children, j, |c| c.required_size().is_some());
+                let child = &mut children[j];
+                child.drop_reason = if child.resolved.min.saturating_add(margin)
+                    > available_after_required
+                {
+                    Some(DropReason::NoSpace)
+                } else {
+                    Some(DropReason::LowerPriority)
+                };
+            }
+        }
+
+        // the space of dropped children asking for it is kept out of the growth
+        for (i, child) in children.iter().enumerate() {
+            if child.size.is_none() && child.resolved.reserve_when_dropped {
+                let margin = self.insertion_margin(children, i, |c| c.size.is_some());
+                available -= child.resolved.min.saturating_add(margin).min(available);
+            }
+        }
+
+        // without stretch, the children don't grow in the remaining space,
+        // but their sizes are still rounded to their steps and to the
+        // size multiple
+        let available = if self.no_stretch {
+            Self::round_to_steps(children);
+            0
+        } else {
+            self.stretch(available, children, leftover_preference)
+        };
+        if self.size_multiple > 1 {
+            self.round_to_multiple(children, available);
+        }
+
+        // overlay children take the whole available size, within their bounds
+        for child in children.iter_mut() {
+            if child.resolved.overlay {
+                let size = child.resolved.max.map_or(total, |max| max.min(total));
+                child.size = Some(size.max(child.resolved.min));
+            }
+        }
+    }
+    /// Make the included children grow in the remaining space: fill
+    /// children first, then towards their targets, then by their grow
+    /// factors, the cells left by the rounding being given last.
+    ///
+    /// Return the space which couldn't be given
+    fn stretch<C>(
+        &self,
+        mut available: usize,
+        children: &mut [Child<C>],
+        leftover_preference: Option<&LeftoverPreferenceFn<C>>,
+    ) -> usize {
+        let fair_floor = self.fair_floor;
+
+        // the fill children take the remaining space, split evenly,
+        // before any other child grows
+        let fills: Vec<usize> = (0..children.len())
+            .filter(|&i| children[i].resolved.fill && children[i].size.is_some())
+            .collect();
+        if !fills.is_empty() {
+            let to_distribute = available;
+            for (k, &i) in fills.iter().enumerate() {
+                let child = &mut children[i];
+                let Some(size) = child.size else {
+                    continue;
+                };
+                let mut growth = to_distribute / fills.len();
+                if k < to_distribute % fills.len() {
+                    growth += 1;
+                }
+                if let Some(max) = child.resolved.max {
+                    growth = growth.min(max.saturating_sub(size));
+                }
+                available -= growth;
+                child.size = Some(size.saturating_add(growth));
+            }
         }
 
+        // the growable children with a target first grow towards it,
+        // proportionally to their grow factor if there's not enough space
+        let needs: Vec<usize> = children
+            .iter()
+            .map(|child| match (child.size, child.resolved.target) {
+                (Some(size), Some(target)) if child.resolved.grow > 0.0 => {
+                    let target = child.resolved.max.map_or(target, |max| target.min(max));
+                    target.saturating_sub(size)
+                }
+                _ => 0,
+            })
+            .collect();
+        let sum_needs = needs.iter().sum::<usize>();
+        if sum_needs > 0 {
+            let to_distribute = available;
+            let sum_weights: f64 = children
+                .iter()
+                .zip(&needs)
+                .map(|(child, &need)| child.resolved.grow * need as f64)
+                .sum();
+            for (child, &need) in children.iter_mut().zip(&needs) {
+                let Some(size) = child.size else {
+                    continue;
+                };
+                let growth = if sum_needs <= to_distribute {
+                    need
+                } else {
+                    let weight = child.resolved.grow * need as f64;
+                    ((to_distribute as f64 * weight / sum_weights) as usize).min(need)
+                }
+                .min(available);
+                available -= growth;
+                child.size = Some(size.saturating_add(growth));
+            }
+        }
+
+        // then we distribute the remaining space to the growable children.
+        // All shares are computed from the same snapshot of the remaining
+        // space so that the result doesn't depend on the order of the children
+        let mut first_pass = true;
+        loop {
+            let to_distribute = available;
+            let mut growths = vec![0.0; children.len()];
+            let mut sum_growths = 0.0;
+            for (i, child) in children.iter().enumerate() {
+                let Some(size) = child.size else {
+                    continue;
+                };
+                growths[i] = child.resolved.grow
+                    * (match child.resolved.max {
+                        None => to_distribute,
+                        Some(max) => max.saturating_sub(size),
+                    } as f64);
+                sum_growths += growths[i];
+            }
+            if sum_growths <= 0.0 {
+                break;
+            }
+            if first_pass {
+                for (child, growth) in children.iter_mut().zip(&growths) {
+                    child.effective_grow = growth / sum_growths;
+                }
+            }
+            let shares: Vec<f64> = growths
+                .iter()
+                .map(|g| g * (to_distribute as f64 / sum_growths))
+                .collect();
+            let mut grown: Vec<usize> = shares.iter().map(|&share| share as usize).collect();
+            // the min growth is only guaranteed in the first pass
+            let min_growth = if first_pass { self.min_growth } else { 0 };
+            if fair_floor > 0.0 || min_growth > 0 {
+                let floors: Vec<usize> = shares
+                    .iter()
+                    .zip(children.iter())
+                    .map(|(&share, child)| {
+                        if share <= 0.0 {
+                            return 0;
+                        }
+                        let fair = ceil(share * fair_floor).min(ceil(share));
+                        let room = match (child.size, child.resolved.max) {
+                            (Some(size), Some(max)) => max.saturating_sub(size),
+                            _ => usize::MAX,
+                        };
+                        fair.max(min_growth.min(room))
+                    })
+                    .collect();
+                let mut spare = to_distribute.saturating_sub(grown.iter().sum::<usize>());
+                // the most starved children are served first
+                let mut order: Vec<usize> = (0..grown.len()).filter(|&i| shares[i] > 0.0).collect();
+                order.sort_by(|&a, &b| {
+                    let ratio = |i: usize| grown[i] as f64 / shares[i];
+                    ratio(a)
+                        .partial_cmp(&ratio(b))
+                        .unwrap_or(core::cmp::Ordering::Equal)
+                });
+                for i in order {
+                    while grown[i] < floors[i] {
+                        if spare > 0 {
+                            spare -= 1;
+                        } else {
+                            let donor = (0..grown.len())
+                                .filter(|&j| grown[j] > floors[j])
+                                .max_by_key(|&j| grown[j] - floors[j]);
+                            let Some(donor) = donor else {
+                                break;
+                            };
+                            grown[donor] -= 1;
+                        }
+                        grown[i] += 1;
+                    }
+                }
+            }
+            for (child, growth) in children.iter_mut().zip(grown) {
+                let Some(size) = child.size else {
+                    continue;
+                };
+                // the f64 shares may sum to slightly more than the
+                // distributed space
+                let growth = match child.resolved.max {
+                    Some(max) => growth.min(max.saturating_sub(size)),
+                    None => growth,
+                }
+                .min(available);
+                available -= growth;
+                child.size = Some(size.saturating_add(growth));
+            }
+            // the space left by the children which reached their max is
+            // distributed again to the other ones (water filling)
+            first_pass = false;
+            if available == 0 || available == to_distribute {
+                break;
+            }
+        }
+        available = available.saturating_add(Self::round_to_steps(children));
+
         // Due to down rounding, it's probable that there's some available space left.
+        let mut order: Vec<usize> = (0..children.len()).collect();
+        if self.reversed {
+            order.reverse();
+        }
+        if self.leftover_strategy == LeftoverStrategy::ByGrow {
+            order.sort_by(|&a, &b| {
+                children[b]
+                    .resolved
+                    .grow
+                    .partial_cmp(&children[a].resolved.grow)
+                    .unwrap_or(core::cmp::Ordering::Equal)
+            });
+        }
+        if let Some(preference) = leftover_preference {
+            let scores: Vec<f64> = children.iter().map(preference).collect();
+            order.sort_by(|&a, &b| {
+                scores[b]
+                    .partial_cmp(&scores[a])
+                    .unwrap_or(core::cmp::Ordering::Equal)
+            });
+        }
+        order.sort_by_key(|&i| children[i].resolved.rounding);
         while available > 0 {
+            let eligible: Vec<usize> = order
+                .iter()
+                .copied()
+                .filter(|&i| {
+                    let child = &children[i];
+                    let step = child.resolved.step.max(1);
+                    child.size.map_or(false, |size| {
+                        child.resolved.grow > 0.0
+                            && step <= available
+                            && child
+                                .resolved
+                                .max
+                                .map_or(true, |max| size.saturating_add(step) <= max)
+                    })
+                })
+                .collect();
+            if eligible.is_empty() {
+                break;
+            }
+            let served: Vec<usize> = match self.leftover_strategy {
+                LeftoverStrategy::RoundRobin if available < eligible.len() => (0..available)
+                    .map(|k| eligible[(2 * k + 1) * eligible.len() / (2 * available)])
+                    .collect(),
+                _ => eligible,
+            };
+            for i in served {
+                let child = &mut children[i];
+                let step = child.resolved.step.max(1);
+                if step > available {
+                    continue;
+                }
+                child.size = child.size.map(|size| size.saturating_add(step));
+                available -= step;
+            }
+        }
+        available
+    }
+    /// Round the sizes of the children with a step down to a multiple of
+    /// it, without going under their min, returning the freed cells
+    fn round_to_steps<C>(children: &mut [Child<C>]) -> usize {
+        let mut freed = 0;
+        for child in children.iter_mut() {
+            let step = child.resolved.step.max(1);
+            let Some(size) = child.size else {
+                continue;
+            };
+            let rounded = size - size % step;
+            if rounded >= child.resolved.min {
+                freed += size - rounded;
+                child.size = Some(rounded);
+            }
+        }
+        freed
+    }
+    /// Round the sizes of the children down to the size multiple, then
+    /// redistribute the saved cells (and the unused ones) by multiples
+    fn round_to_multiple<C>(&self, children: &mut [Child<C>], mut available: usize) {
+        let multiple = self.size_multiple;
+        for child in children.iter_mut() {
+            if let Some(size) = child.size {
+                available = available.saturating_add(size % multiple);
+                let rounded = size - size % multiple;
+                child.size = Some(rounded);
+                // the resolved min is the one the size was rounded to
+                child.resolved.min = child.resolved.min.min(rounded);
+            }
+        }
+        while available >= multiple {
             let mut given = 0;
-            for child in &mut children {
+            for child in children.iter_mut() {
                 let Some(size) = child.size else {
                     continue;
                 };
-                if child.constraints.max.map_or(true, |max| size < max) {
-                    child.size = Some(size + 1);
+                if child.resolved.max.map_or(true, |max| size.saturating_add(multiple) <= max) {
+                    child.size = Some(size.saturating_add(multiple));
                     given += 1;
-                    available -= 1;
-                    if available == 0 {
+                    available -= multiple;
+                    if available < multiple {
                         break;
                     }
                 }
@@ -205,9 +2033,6 @@ impl<C> ContainerBuilder<C> {
                 break;
             }
         }
-
-        let con = Container { children };
-        Ok(con)
     }
 }
 
@@ -217,7 +2042,12 @@ impl<C> Child<C> {
         Child {
             content,
             constraints,
+            resolved: constraints,
+            min_of_available: None,
             size: None,
+            drop_reason: None,
+            effective_grow: 0.0,
+            rank: None,
         }
     }
     pub fn content(&self) -> &C {
@@ -226,49 +2056,919 @@ impl<C> Child<C> {
     pub fn optional(self) -> Self {
         self.optional_with_priority(0)
     }
+    /// Make the child optional: it's included only if it fits, after the
+    /// optional children of higher priority.
+    ///
+    /// Optional children of the same priority are considered in declaration
+    /// order (or in the reverse order, with `with_reversed(true)`):
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let builder = || {
+    ///     Container::builder_in(20)
+    ///         .with(Child::new("a").with_size(8))
+    ///         .with(Child::new("b").with_size(10).optional_with_priority(3))
+    ///         .with(Child::new("c").with_size(10).optional_with_priority(3))
+    /// };
+    /// assert_eq!(builder().build().unwrap().sizes(), vec![8, 10, 0]);
+    /// let container = builder().with_reversed(true).build().unwrap();
+    /// assert_eq!(container.sizes(), vec![8, 0, 10]);
+    /// ```
     pub fn optional_with_priority(mut self, priority: usize) -> Self {
         self.constraints.optionality = Optionality::Optional { priority };
         self
     }
+    /// Make the child optional, with a value used instead of the priority
+    /// when the container uses `OptionalStrategy::ValueDensity`
+    pub fn optional_with_value(mut self, value: f64) -> Self {
+        self.constraints.optionality = Optionality::Optional { priority: 0 };
+        self.constraints.value = value;
+        self
+    }
+    /// Make the child always included, but allow it to shrink from its min
+    /// down to `floor` when optional children of higher priority need space
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(30)
+    ///     .with(Child::new("a").with_size(10))
+    ///     .with(Child::new("b").with_size(15).squeezable(5, 5))
+    ///     .with(Child::new("c").with_size(15).optional_with_priority(8))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![10, 5, 15]);
+    ///
+    /// let container = Container::builder_in(30)
+    ///     .with(Child::new("a").with_size(10))
+    ///     .with(Child::new("b").with_size(15).squeezable(5, 5))
+    ///     .with(Child::new("c").with_size(15).optional_with_priority(3))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![10, 15, 0]);
+    /// ```
+    pub fn squeezable(mut self, priority: usize, floor: usize) -> Self {
+        self.constraints.optionality = Optionality::Squeezable { priority, floor };
+        self
+    }
+    /// When `reserve` is true and the child is dropped, keep its min size
+    /// (and the margin before it) unused instead of giving it to the other
+    /// children, so that the layout doesn't change much when it's included
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let builder = |reserve| {
+    ///     Container::builder_in(30)
+    ///         .with_margin_between(1)
+    ///         .with_trailing_fill(true)
+    ///         .with(Child::new("a").with_min(10))
+    ///         .with(Child::new("b").with_size(12).optional_with_priority(5))
+    ///         .with(Child::new("c").with_size(8).optional().reserve_when_dropped(reserve))
+    /// };
+    /// let container = builder(false).build().unwrap();
+    /// assert_eq!(container.sizes(), vec![17, 12, 0]);
+    /// let container = builder(true).build().unwrap();
+    /// assert_eq!(container.sizes(), vec![10, 12, 0]);
+    /// assert_eq!(container.trailing_fill(), Some((23, 7)));
+    /// ```
+    pub fn reserve_when_dropped(mut self, reserve: bool) -> Self {
+        self.constraints.reserve_when_dropped = reserve;
+        self
+    }
+    /// Put the child in a group, so that the inter group margin,
+    /// if any, only applies at group boundaries
+    pub fn in_group(mut self, group: usize) -> Self {
+        self.constraints.group = Some(group);
+        self
+    }
+    /// Set the margin between this child and the next included one,
+    /// instead of the margin between children of the container
+    /// (there's no margin after the last included child)
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let builder = |width: usize| {
+    ///     Container::builder_in(width)
+    ///         .with_margin_between(1)
+    ///         .with(Child::new("a").with_size(5))
+    ///         .with(Child::new("b").with_size(5).with_margin_after(3))
+    ///         .with(Child::new("c").with_size(5).optional())
+    /// };
+    /// let container = builder(19).build().unwrap();
+    /// assert_eq!(container.sizes(), vec![5, 5, 5]);
+    /// assert_eq!(container.offsets(), vec![0, 6, 14]);
+    /// let container = builder(18).build().unwrap();
+    /// assert_eq!(container.sizes(), vec![5, 5, 0]);
+    /// assert_eq!(container.used(), 11);
+    /// ```
+    pub fn with_margin_after(mut self, margin: usize) -> Self {
+        self.constraints.margin_after = Some(margin);
+        self
+    }
+    /// Put the child in a bundle: the optional children of a bundle are
+    /// included together, when the first of them in the order of inclusion
+    /// comes, or not at all
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let builder = |bundled: bool| {
+    ///     let in_bundle = |child: Child<&'static str>| {
+    ///         if bundled { child.in_bundle(1) } else { child }
+    ///     };
+    ///     Container::builder_in(25)
+    ///         .with(Child::new("name").with_min(10))
+    ///         .with(in_bundle(Child::new("price").with_size(8).optional_with_priority(5)))
+    ///         .with(in_bundle(Child::new("currency").with_size(9).optional_with_priority(2)))
+    ///         .with(Child::new("notes").with_size(6).optional_with_priority(3))
+    /// };
+    /// assert_eq!(builder(false).build().unwrap().sizes(), vec![11, 8, 0, 6]);
+    /// assert_eq!(builder(true).build().unwrap().sizes(), vec![19, 0, 0, 6]);
+    /// ```
+    pub fn in_bundle(mut self, bundle: usize) -> Self {
+        self.constraints.bundle = Some(bundle);
+        self
+    }
+    /// Put the child in a category: the most important optional child of
+    /// a category is considered before the other optional children, so that
+    /// no category is left without an included child when it can be avoided
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(30)
+    ///     .with(Child::new("a").with_size(10))
+    ///     .with(Child::new("b").with_size(8).optional_with_priority(9).category(1))
+    ///     .with(Child::new("c").with_size(8).optional_with_priority(8).category(1))
+    ///     .with(Child::new("d").with_size(8).optional_with_priority(1).category(2))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![10, 8, 0, 8]);
+    /// ```
+    pub fn category(mut self, category: usize) -> Self {
+        self.constraints.category = Some(category);
+        self
+    }
     pub fn with_min(mut self, min: usize) -> Self {
         self.constraints.min = min;
+        self.min_of_available = None;
+        self.constraints.fraction = None;
         self
     }
+    /// Compute the min size from the available size, every time the
+    /// container is laid out
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let builder = |available| {
+    ///     Container::builder_in(available)
+    ///         .with(Child::new("a").with_min_of_available(|w| if w < 40 { 0 } else { 10 }))
+    ///         .with(Child::new("b").with_min(30))
+    /// };
+    /// let container = builder(39).build().unwrap();
+    /// assert_eq!(container.children[0].resolved_min(), 0);
+    /// assert_eq!(container.sizes(), vec![5, 34]);
+    /// let container = builder(40).build().unwrap();
+    /// assert_eq!(container.children[0].resolved_min(), 10);
+    /// assert_eq!(container.sizes(), vec![10, 30]);
+    /// ```
+    pub fn with_min_of_available<F>(mut self, f: F) -> Self
+    where
+        F: Fn(usize) -> usize + Send + Sync + 'static,
+    {
+        self.min_of_available = Some(Arc::new(f));
+        self.constraints.fraction = None;
+        self
+    }
+    /// Set the min to the given fraction of the available size, rounded,
+    /// every time the container is laid out.
+    ///
+    /// The child still grows like the other ones in the remaining space,
+    /// unless its grow is set to 0:
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let builder = |grow| {
+    ///     Container::builder_in(100)
+    ///         .with(Child::new("half").with_fraction(0.5).with_grow(grow))
+    ///         .with(Child::new("a").with_size(20))
+    ///         .with(Child::new("b").with_min(10))
+    /// };
+    /// assert_eq!(builder(0.0).build().unwrap().sizes(), vec![50, 20, 30]);
+    /// assert_eq!(builder(1.0).build().unwrap().sizes(), vec![60, 20, 20]);
+    /// ```
+    ///
+    /// The mins coming from fractions are reduced when they would prevent
+    /// the required children from fitting:
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(100)
+    ///     .with(Child::new("most").with_fraction(0.8))
+    ///     .with(Child::new("a").with_size(20))
+    ///     .with(Child::new("b").with_size(10))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![70, 20, 10]);
+    /// ```
+    pub fn with_fraction(mut self, fraction: f64) -> Self {
+        self.constraints.fraction = Some(fraction);
+        self.min_of_available = None;
+        self
+    }
+    /// Set the max size of the child. The space a growing child can't take
+    /// because of its max goes to the other growable children, by their
+    /// grow factor
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(40)
+    ///     .with(Child::new("a").with_max(2).with_grow(10.0))
+    ///     .with(Child::new("b").with_grow(3.0))
+    ///     .with(Child::new("c"))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![2, 29, 9]);
+    /// ```
     pub fn with_max(mut self, max: usize) -> Self {
         self.constraints.max = Some(max);
         self
     }
+    /// Set the max from the content, so that the child never grows wider
+    /// than what its content needs
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(40)
+    ///     .with(Child::new("short").with_min(3).max_from_content(|s| s.len()))
+    ///     .with(Child::new("a much longer content").with_min(3))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![5, 35]);
+    /// ```
+    pub fn max_from_content<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&C) -> usize,
+    {
+        self.constraints.max = Some(f(&self.content));
+        self
+    }
     pub fn clamp(mut self, min: usize, max: usize) -> Self {
         self.constraints.min = min;
+        self.min_of_available = None;
+        self.constraints.fraction = None;
         self.constraints.max = Some(max);
         self
     }
     pub fn with_size(mut self, size: usize) -> Self {
         self.constraints.min = size;
+        self.min_of_available = None;
+        self.constraints.fraction = None;
         self.constraints.max = Some(size);
         self
     }
+    /// Set the factor weighting the share of the remaining space this
+    /// child gets (default is 1.0). A child with a grow of 0 never grows.
+    ///
+    /// Shares don't depend on the order of the children:
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(14)
+    ///     .with(Child::new("x").with_min(2))
+    ///     .with(Child::new("y").with_min(4).with_grow(3.0))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![4, 10]);
+    /// let container = Container::builder_in(14)
+    ///     .with(Child::new("y").with_min(4).with_grow(3.0))
+    ///     .with(Child::new("x").with_min(2))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![10, 4]);
+    ///
+    /// // the rounded shares never exceed the remaining space
+    /// let container = Container::builder_in(usize::MAX)
+    ///     .with(Child::new("x"))
+    ///     .with(Child::new("y"))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.used(), usize::MAX);
+    /// ```
+    ///
+    /// Children with equal grow factors split the space as evenly as
+    /// the rounding allows:
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(17)
+    ///     .with(Child::new("a").with_min(3))
+    ///     .with(Child::new("b").with_min(3))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![9, 8]);
+    /// let container = Container::builder_in(17)
+    ///     .with(Child::new("b").with_min(3))
+    ///     .with(Child::new("a").with_min(3))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![9, 8]);
+    /// ```
+    ///
+    /// A child with a grow of 0 doesn't get the cells left by the rounding
+    /// either, even without max:
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(23)
+    ///     .with(Child::new("fixed").with_min(8).with_grow(0.0))
+    ///     .with(Child::new("a").with_min(3))
+    ///     .with(Child::new("b").with_min(3))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![8, 8, 7]);
+    /// ```
     pub fn with_grow(mut self, grow: f64) -> Self {
         self.constraints.grow = grow;
         self
     }
+    /// Set a size the child grows to (proportionally to its grow factor
+    /// if there's not enough space) before any child grows further
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let builder = |available| {
+    ///     Container::builder_in(available)
+    ///         .with(Child::new("a").with_min(2).with_target(10))
+    ///         .with(Child::new("b").with_min(2).with_target(6).with_grow(3.0))
+    /// };
+    /// assert_eq!(builder(16).build().unwrap().sizes(), vec![10, 6]);
+    /// assert_eq!(builder(30).build().unwrap().sizes(), vec![14, 16]);
+    /// ```
+    pub fn with_target(mut self, target: usize) -> Self {
+        self.constraints.target = Some(target);
+        self
+    }
+    /// Make the child float over the other ones: it's always included,
+    /// at offset 0, as big as the available size allows within its min
+    /// and max, and it takes no space from the other children
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let builder = |overlay| {
+    ///     Container::builder_in(30)
+    ///         .with_margin_between(1)
+    ///         .with(Child::new("a").with_size(10))
+    ///         .with(Child::new("popup").clamp(5, 12).overlay(overlay))
+    ///         .with(Child::new("b").with_min(5))
+    /// };
+    /// let container = builder(false).build().unwrap();
+    /// assert_eq!(container.sizes(), vec![10, 9, 9]);
+    /// let container = builder(true).build().unwrap();
+    /// assert_eq!(container.sizes(), vec![10, 12, 19]);
+    /// assert_eq!(container.offsets(), vec![0, 0, 11]);
+    /// assert_eq!(container.used(), 30);
+    /// ```
+    pub fn overlay(mut self, overlay: bool) -> Self {
+        self.constraints.overlay = overlay;
+        self
+    }
+    /// Round the size of the child down to a multiple of `step` (unless
+    /// this would make it smaller than its min), the cells this frees
+    /// going to the other children
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let builder = |step| {
+    ///     Container::builder_in(14)
+    ///         .with(Child::new("a").with_min(3).with_step(step))
+    ///         .with(Child::new("b").with_min(3))
+    /// };
+    /// assert_eq!(builder(1).build().unwrap().sizes(), vec![7, 7]);
+    /// let container = builder(2).build().unwrap();
+    /// assert_eq!(container.sizes(), vec![6, 8]);
+    /// assert_eq!(container.used(), 14);
+    /// ```
+    pub fn with_step(mut self, step: usize) -> Self {
+        self.constraints.step = step;
+        self
+    }
+    /// Make the child take all the remaining space (up to its max, if any)
+    /// instead of letting the other children grow, the remaining space
+    /// being split evenly between the fill children
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(100)
+    ///     .with_margin_between(1)
+    ///     .with(Child::new("a").with_min(10))
+    ///     .with(Child::new("b").with_min(20))
+    ///     .with(Child::new("spacer").fill())
+    ///     .with(Child::new("c").with_min(5))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![10, 20, 100 - 35 - 3, 5]);
+    /// ```
+    pub fn fill(mut self) -> Self {
+        self.constraints.fill = true;
+        self
+    }
+    /// Set whether the child gets the cells left by the rounding of the
+    /// shares before or after the other ones (default is `Nearest`)
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container, Rounding};
+    ///
+    /// let container = Container::builder_in(11)
+    ///     .with(Child::new("a"))
+    ///     .with(Child::new("b").with_rounding(Rounding::Up))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![5, 6]);
+    /// ```
+    pub fn with_rounding(mut self, rounding: Rounding) -> Self {
+        self.constraints.rounding = rounding;
+        self
+    }
+    /// Set the factor weighting how much this child shrinks below its min
+    /// when the required children don't fit (default is 0.0, the child
+    /// never shrinks). It's independent from the grow factor.
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let builder = |available| {
+    ///     Container::builder_in(available)
+    ///         .with(Child::new("a").with_min(10).with_grow(3.0))
+    ///         .with(Child::new("b").with_min(10).with_shrink(1.0))
+    /// };
+    /// assert_eq!(builder(40).build().unwrap().sizes(), vec![25, 15]);
+    /// assert_eq!(builder(15).build().unwrap().sizes(), vec![10, 5]);
+    /// assert!(builder(9).build().is_err());
+    /// ```
+    pub fn with_shrink(mut self, shrink: f64) -> Self {
+        self.constraints.shrink = shrink;
+        self
+    }
+    /// Set the size under which the child never shrinks (default is 0).
+    ///
+    /// When even the shrink floors don't fit, the layout fails.
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let builder = |available| {
+    ///     Container::builder_in(available)
+    ///         .with_margin_between(1)
+    ///         .with(Child::new("a").with_min(10))
+    ///         .with(Child::new("b").with_min(10).with_shrink(1.0).with_shrink_floor(4))
+    ///         .with(Child::new("c").with_min(20).with_shrink(1.0).with_shrink_floor(4))
+    /// };
+    /// assert_eq!(builder(32).build().unwrap().sizes(), vec![10, 7, 13]);
+    /// assert_eq!(builder(20).build().unwrap().sizes(), vec![10, 4, 4]);
+    /// assert!(builder(19).build().is_err());
+    /// ```
+    pub fn with_shrink_floor(mut self, floor: usize) -> Self {
+        self.constraints.shrink_floor = floor;
+        self
+    }
+    /// Return the constraints of the child, as declared
+    ///
+    /// ```
+    /// use flex_grow::{Child, ChildConstraints, Optionality};
+    ///
+    /// let child = Child::new("a").clamp(3, 8).optional_with_priority(7);
+    /// let constraints = ChildConstraints {
+    ///     min: 3,
+    ///     max: Some(8),
+    ///     optionality: Optionality::Optional { priority: 7 },
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(child.constraints(), constraints);
+    /// ```
     pub fn constraints(&self) -> ChildConstraints {
         self.constraints
     }
-    fn is_optional(&self) -> bool {
-        matches!(self.constraints.optionality, Optionality::Optional { .. })
+    /// Return a copy of the child without content, not yet laid out,
+    /// to try layouts on
+    fn without_content(&self) -> Child<()> {
+        Child {
+            content: (),
+            constraints: self.constraints,
+            resolved: self.constraints,
+            min_of_available: self.min_of_available.clone(),
+            size: None,
+            drop_reason: None,
+            effective_grow: 0.0,
+            rank: None,
+        }
+    }
+    /// Return the size the child takes in the first pass, or none if it's optional
+    fn required_size(&self) -> Option<usize> {
+        if self.resolved.overlay {
+            return None;
+        }
+        match self.resolved.optionality {
+            Optionality::Required => Some(self.resolved.min),
+            Optionality::Optional { .. } => None,
+            Optionality::Squeezable { floor, .. } => Some(floor.min(self.resolved.min)),
+        }
+    }
+    /// Return the smallest size the layout may give to the child
+    fn lowest_size(&self) -> usize {
+        let mut lowest = match self.resolved.optionality {
+            Optionality::Squeezable { floor, .. } => floor.min(self.resolved.min),
+            _ => self.resolved.min,
+        };
+        if self.resolved.shrink > 0.0 {
+            lowest = lowest.min(self.resolved.shrink_floor);
+        }
+        lowest
+    }
+    /// Return the priority of the child, if it's optional or squeezable
+    fn priority(&self) -> Option<usize> {
+        if self.resolved.overlay {
+            return None;
+        }
+        match self.resolved.optionality {
+            Optionality::Required => None,
+            Optionality::Optional { priority } => Some(priority),
+            Optionality::Squeezable { priority, .. } => Some(priority),
+        }
+    }
+    /// Record the position at which the allocation considers the child,
+    /// unless it was already considered
+    fn consider(&mut self, next_rank: &mut usize) {
+        if self.rank.is_none() {
+            self.rank = Some(*next_rank);
+            *next_rank += 1;
+        }
+    }
+    /// Return the value per cell of min size
+    fn value_density(&self) -> f64 {
+        if self.resolved.min == 0 {
+            f64::INFINITY
+        } else {
+            self.resolved.value / self.resolved.min as f64
+        }
     }
     /// Return the size, if the child is included in the container, or none
     /// if there wasn't enough space to include it
     pub fn size(&self) -> Option<usize> {
         self.size
     }
+    /// Return the fraction of the space remaining after the mins which
+    /// was given to this child, before rounding, that is its grow factor
+    /// weighted by its room to grow and normalized over all children
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(20)
+    ///     .with(Child::new("a").with_max(5))
+    ///     .with(Child::new("b"))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![4, 16]);
+    /// assert_eq!(container.children[0].constraints().grow, 1.0);
+    /// assert_eq!(container.children[0].effective_grow(), 0.2);
+    /// assert_eq!(container.children[1].effective_grow(), 0.8);
+    /// ```
+    pub fn effective_grow(&self) -> f64 {
+        self.effective_grow
+    }
+    /// Tell whether the child is included in the container
+    pub fn is_included(&self) -> bool {
+        self.size.is_some()
+    }
+    /// Return the min used by the last layout, once resolved from the
+    /// available size (see `with_min_of_available` and `with_fraction`)
+    pub fn resolved_min(&self) -> usize {
+        self.resolved.min
+    }
+    /// Return the max used by the last layout
+    pub fn resolved_max(&self) -> Option<usize> {
+        self.resolved.max
+    }
+    /// Return how many cells the child got over its resolved min,
+    /// 0 if it isn't included
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(30)
+    ///     .with(Child::new("a").with_size(10))
+    ///     .with(Child::new("b").with_fraction(0.2))
+    ///     .build()
+    ///     .unwrap();
+    /// let b = &container.children[1];
+    /// assert_eq!(b.resolved_min(), 6);
+    /// assert_eq!(b.resolved_max(), None);
+    /// assert_eq!(b.size(), Some(20));
+    /// assert_eq!(b.grew_by(), 14);
+    /// ```
+    pub fn grew_by(&self) -> usize {
+        self.size
+            .map_or(0, |size| size.saturating_sub(self.resolved.min))
+    }
+    /// Return why the child isn't included, if it isn't
+    pub fn drop_reason(&self) -> Option<DropReason> {
+        self.drop_reason
+    }
+}
+
+impl Container<()> {
+    /// Build a container of `count` columns of equal size (differing
+    /// by at most 1) separated by `margin`
+    ///
+    /// ```
+    /// use flex_grow::Container;
+    ///
+    /// let container = Container::equal_columns(32, 3, 1).unwrap();
+    /// assert_eq!(container.sizes(), vec![10, 10, 10]);
+    /// let container = Container::equal_columns(33, 3, 1).unwrap();
+    /// assert_eq!(container.sizes(), vec![11, 10, 10]);
+    /// ```
+    pub fn equal_columns(available: usize, count: usize, margin: usize) -> Result<Self, Error> {
+        let mut builder = Container::builder_in(available).with_margin_between(margin);
+        for _ in 0..count {
+            builder.add(Child::new(()));
+        }
+        builder.build()
+    }
+}
+
+impl<C> Extend<Child<C>> for ContainerBuilder<C> {
+    fn extend<I: IntoIterator<Item = Child<C>>>(&mut self, children: I) {
+        self.add_all(children);
+    }
+}
+
+impl<C: fmt::Debug> fmt::Debug for ContainerBuilder<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ContainerBuilder")
+            .field("available", &self.available)
+            .field("settings", &self.settings)
+            .field("children", &self.children)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<C: fmt::Debug> fmt::Debug for Child<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Child")
+            .field("content", &self.content)
+            .field("constraints", &self.constraints)
+            .field("size", &self.size)
+            .field("drop_reason", &self.drop_reason)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<C: fmt::Debug> fmt::Debug for Container<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Container")
+            .field("available", &self.available)
+            .field("settings", &self.settings)
+            .field("children", &self.children)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The clone keeps the sizes, but not the reflow callback
+///
+/// ```
+/// use flex_grow::{Child, Container};
+///
+/// let container = Container::builder_in(30)
+///     .with_margin_between(1)
+///     .with(Child::new("a").with_size(10))
+///     .with(Child::new("b").with_size(30).optional())
+///     .with(Child::new("c").with_min(5))
+///     .build()
+///     .unwrap();
+/// let mut narrower = container.clone();
+/// assert_eq!(narrower.sizes(), container.sizes());
+/// narrower.reflow(20).unwrap();
+/// assert_eq!(narrower.sizes(), vec![10, 0, 9]);
+/// assert_eq!(container.sizes(), vec![10, 0, 19]);
+/// assert!(format!("{:?}", container).starts_with("Container {"));
+/// ```
+impl<C: Clone> Clone for Container<C> {
+    fn clone(&self) -> Self {
+        Container {
+            children: self.children.clone(),
+            available: self.available,
+            settings: self.settings,
+            leftover_preference: self.leftover_preference.clone(),
+            on_reflow: None,
+        }
+    }
+}
+
+impl<C: fmt::Display> Container<C> {
+    /// Return a two rows string showing the included children, with their
+    /// centered content on the first row and their size on the second one,
+    /// and a `|` in the margins between them
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(50)
+    ///     .with_margin_between(1)
+    ///     .with(Child::new("name").clamp(5, 10))
+    ///     .with(Child::new("price").with_size(8).optional_with_priority(7))
+    ///     .with(Child::new("quantity").with_size(8).optional())
+    ///     .with(Child::new("total").with_size(8))
+    ///     .with(Child::new("comments").with_min(10).with_grow(2.0))
+    ///     .with(Child::new("vendor").with_size(60).optional_with_priority(9))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     container.debug_table(),
+    ///     concat!(
+    ///         " name  | price  |quantity| total  |   comments    \n",
+    ///         "   7   |   8    |   8    |   8    |      15       ",
+    ///     ),
+    /// );
+    ///
+    /// // without margins, the rows are exactly as wide as the container
+    /// let container = Container::builder_in(10)
+    ///     .with(Child::new("a").with_size(4))
+    ///     .with(Child::new("b").with_min(6))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.debug_table(), " a    b   \n 4    6   ");
+    ///
+    /// let container = Container::builder_in(14)
+    ///     .with_margin_around(2)
+    ///     .with(Child::new("a").with_size(4))
+    ///     .with(Child::new("b").with_min(6))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.debug_table(), "   a    b   \n   4    6   ");
+    /// ```
+    pub fn debug_table(&self) -> String {
+        let offsets = self.offsets();
+        let row = |cell: &dyn Fn(&Child<C>, usize) -> String| {
+            let mut line = String::new();
+            let mut end = 0;
+            let mut added = false;
+            for (child, &offset) in self.children.iter().zip(&offsets) {
+                let Some(size) = child.size else {
+                    continue;
+                };
+                if child.constraints.overlay {
+                    continue;
+                }
+                let gap = offset.saturating_sub(end);
+                if added {
+                    if gap > 0 {
+                        line.push_str(&format!("{:^gap$}", "|", gap = gap));
+                    }
+                } else {
+                    // the margin before the first child
+                    line.push_str(&" ".repeat(gap));
+                    added = true;
+                }
+                line.push_str(&format!("{:^size$.size$}", cell(child, size), size = size));
+                end = offset + size;
+            }
+            line
+        };
+        let contents = row(&|child, _| child.content.to_string());
+        let sizes = row(&|_, size| size.to_string());
+        format!("{}\n{}", contents, sizes)
+    }
+}
+
+impl<C: Clone> Child<C> {
+    /// Return a copy of this child with the same content and constraints,
+    /// but not yet laid out
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let name = Child::new("name").with_min(5);
+    /// let wide = Container::builder_in(30)
+    ///     .with(name.clone_constraints())
+    ///     .build()
+    ///     .unwrap();
+    /// let narrow = Container::builder_in(7)
+    ///     .with(name.clone_constraints())
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(wide.sizes(), vec![30]);
+    /// assert_eq!(narrow.sizes(), vec![7]);
+    /// assert_eq!(name.size(), None);
+    /// ```
+    pub fn clone_constraints(&self) -> Child<C> {
+        Child {
+            content: self.content.clone(),
+            constraints: self.constraints,
+            resolved: self.constraints,
+            min_of_available: self.min_of_available.clone(),
+            size: None,
+            drop_reason: None,
+            effective_grow: 0.0,
+            rank: None,
+        }
+    }
 }
 
 impl<C> Container<C> {
     pub fn builder_in(available: usize) -> ContainerBuilder<C> {
         ContainerBuilder::with_available(available)
     }
+    /// Set a callback to call with the new sizes after every successful reflow
+    pub fn with_reflow_callback<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(&[usize]) + Send + Sync + 'static,
+    {
+        self.on_reflow = Some(Box::new(callback));
+        self
+    }
+    /// Lay out the children again, with the same settings, in a new available size
+    ///
+    /// On error, the container is left unchanged.
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let received = Arc::new(Mutex::new(Vec::new()));
+    /// let sink = Arc::clone(&received);
+    /// let mut container = Container::builder_in(30)
+    ///     .with_margin_between(1)
+    ///     .with(Child::new("a").with_size(10))
+    ///     .with(Child::new("b").with_min(5))
+    ///     .with(Child::new("c").with_size(8).optional())
+    ///     .build()
+    ///     .unwrap()
+    ///     .with_reflow_callback(move |sizes| sink.lock().unwrap().push(sizes.to_vec()));
+    /// assert_eq!(container.sizes(), vec![10, 10, 8]);
+    /// container.reflow(20).unwrap();
+    /// container.reflow(40).unwrap();
+    /// assert!(container.reflow(12).is_err());
+    /// assert_eq!(container.sizes(), vec![10, 20, 8]);
+    /// assert_eq!(container.available(), 40);
+    /// assert_eq!(*received.lock().unwrap(), vec![vec![10, 9, 0], vec![10, 20, 8]]);
+    /// ```
+    ///
+    /// The optional children dropped in a smaller size come back
+    /// when there's enough space again:
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let mut container = Container::builder_in(80)
+    ///     .with_margin_between(1)
+    ///     .with(Child::new("name").with_min(20))
+    ///     .with(Child::new("size").with_size(10).optional_with_priority(5))
+    ///     .with(Child::new("date").with_size(16).optional_with_priority(3))
+    ///     .with(Child::new("comments").with_min(20).optional_with_priority(1))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![26, 10, 16, 25]);
+    /// container.reflow(40).unwrap();
+    /// assert_eq!(container.sizes(), vec![29, 10, 0, 0]);
+    /// container.reflow(80).unwrap();
+    /// assert_eq!(container.sizes(), vec![26, 10, 16, 25]);
+    /// assert_eq!(*container.children[3].content(), "comments");
+    /// ```
+    pub fn reflow(&mut self, available: usize) -> Result<(), Error> {
+        let settings = self.settings.resolved(available);
+        self.available = settings.layout(
+            available,
+            &mut self.children,
+            self.leftover_preference.as_deref(),
+        )?;
+        self.settings = settings;
+        let sizes = self.sizes();
+        if let Some(callback) = &mut self.on_reflow {
+            callback(&sizes);
+        }
+        Ok(())
+    }
+    /// Return the available size the children were laid out in
+    pub fn available(&self) -> usize {
+        self.available
+    }
     /// Return the sizes of the children, in the order they were added,
     /// with 0 for non-included children
     pub fn sizes(&self) -> Vec<usize> {
@@ -277,9 +2977,773 @@ impl<C> Container<C> {
             .map(|sc| sc.size.unwrap_or(0))
             .collect()
     }
+    /// Return the sizes like `sizes()`, but only if they're still valid,
+    /// which they may not be after a manual change: they must fit in the
+    /// available size and respect the constraints of the children
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container, Error};
+    ///
+    /// let mut container = Container::builder_in(20)
+    ///     .with(Child::new("a").clamp(5, 12))
+    ///     .with(Child::new("b"))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.try_sizes().unwrap(), vec![10, 10]);
+    /// container.map_sizes(|size| size + 1);
+    /// assert_eq!(container.sizes(), vec![11, 11]);
+    /// assert!(matches!(
+    ///     container.try_sizes(),
+    ///     Err(Error::NotEnoughSpace { needed: 22, available: 20, child_index: 1 }),
+    /// ));
+    /// container.map_sizes(|size| size - 8);
+    /// assert!(matches!(
+    ///     container.try_sizes(),
+    ///     Err(Error::SizeOutOfBounds { index: 0, size: 3 }),
+    /// ));
+    ///
+    /// // the sizes are checked against the bounds the layout used
+    /// let container = Container::builder_in(20)
+    ///     .with_scale(0.5)
+    ///     .with(Child::new("a").with_size(10))
+    ///     .with(Child::new("b"))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.try_sizes().unwrap(), vec![5, 15]);
+    /// let container = Container::builder_in(5)
+    ///     .with_size_multiple(2)
+    ///     .with(Child::new("a").with_min(5))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.try_sizes().unwrap(), vec![4]);
+    /// ```
+    pub fn try_sizes(&self) -> Result<Vec<usize>, Error> {
+        let used = self.used();
+        if used > self.available {
+            // the first child ending after the available size
+            let around = self.settings.margin_around;
+            let (child_index, needed) = self
+                .offsets()
+                .into_iter()
+                .zip(&self.children)
+                .enumerate()
+                .filter(|(_, (_, child))| !child.constraints.overlay)
+                .filter_map(|(i, (offset, child))| {
+                    child.size.map(|size| (i, offset + size + around))
+                })
+                .find(|&(_, end)| end > self.available)
+                .unwrap_or((0, used));
+            return Err(Error::NotEnoughSpace {
+                needed,
+                available: self.available,
+                child_index,
+            });
+        }
+        for (index, child) in self.children.iter().enumerate() {
+            let Some(size) = child.size else {
+                continue;
+            };
+            let too_big = child.resolved.max.map_or(false, |max| size > max);
+            if too_big || size < child.lowest_size() {
+                return Err(Error::SizeOutOfBounds { index, size });
+            }
+        }
+        Ok(self.sizes())
+    }
+    /// Return the sizes of the children, in the reverse of the order they
+    /// were added, with 0 for non-included children
+    pub fn sizes_rev(&self) -> Vec<usize> {
+        let mut sizes = self.sizes();
+        sizes.reverse();
+        sizes
+    }
+    /// Return, for each child in the order they were added, how many more
+    /// cells it would need to reach its desired size (0 for non-included
+    /// children)
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(20)
+    ///     .with(Child::new("description!").with_size(8))
+    ///     .with(Child::new("name").with_min(4))
+    ///     .with(Child::new("comments").with_size(20).optional())
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![8, 12, 0]);
+    /// let desired = |content: &&str| content.len();
+    /// assert_eq!(container.unmet_demand(desired), vec![4, 0, 0]);
+    /// ```
+    pub fn unmet_demand<F>(&self, desired: F) -> Vec<usize>
+    where
+        F: Fn(&C) -> usize,
+    {
+        self.children
+            .iter()
+            .map(|child| match child.size {
+                Some(size) => desired(&child.content).saturating_sub(size),
+                None => 0,
+            })
+            .collect()
+    }
+    /// Return the sizes of the children, in the order they were added,
+    /// with none for non-included children
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(10)
+    ///     .with(Child::new("a").with_size(10))
+    ///     .with(Child::new("b").with_max(0))
+    ///     .with(Child::new("c").with_size(3).optional())
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![10, 0, 0]);
+    /// assert_eq!(container.sized(), vec![Some(10), Some(0), None]);
+    /// ```
+    pub fn sized(&self) -> Vec<Option<usize>> {
+        self.children.iter().map(|sc| sc.size).collect()
+    }
+    /// Return the indices of the children which are included in this
+    /// container but weren't in `previous`, and the indices of the ones
+    /// which were included in `previous` but aren't anymore
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let builder = |available| {
+    ///     Container::builder_in(available)
+    ///         .with_margin_between(1)
+    ///         .with(Child::new("name").clamp(5, 10))
+    ///         .with(Child::new("total").with_size(8))
+    ///         .with(Child::new("comments").with_min(10).optional_with_priority(3))
+    ///         .with(Child::new("vendor").with_size(25).optional_with_priority(9))
+    /// };
+    /// let wide = builder(50).build().unwrap();
+    /// let narrow = builder(30).build().unwrap();
+    /// assert_eq!(wide.sizes(), vec![10, 8, 0, 25]);
+    /// assert_eq!(narrow.sizes(), vec![8, 8, 12, 0]);
+    /// assert_eq!(narrow.inclusion_delta(&wide), (vec![2], vec![3]));
+    /// ```
+    pub fn inclusion_delta(&self, previous: &Container<C>) -> (Vec<usize>, Vec<usize>) {
+        let mut included = Vec::new();
+        let mut dropped = Vec::new();
+        for (i, child) in self.children.iter().enumerate() {
+            let was_included = previous.children.get(i).map_or(false, |c| c.size.is_some());
+            match (child.size.is_some(), was_included) {
+                (true, false) => included.push(i),
+                (false, true) => dropped.push(i),
+                _ => {}
+            }
+        }
+        (included, dropped)
+    }
+    /// Return `steps + 1` size vectors going linearly from the sizes of
+    /// this container to the ones of the target, for an animation.
+    ///
+    /// Dropped children are considered of size 0, so that they shrink
+    /// to nothing (or grow from nothing) instead of vanishing suddenly.
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let builder = |available| {
+    ///     Container::builder_in(available)
+    ///         .with(Child::new("a").with_min(10))
+    ///         .with(Child::new("b").with_size(8).optional())
+    /// };
+    /// let wide = builder(30).build().unwrap();
+    /// let narrow = builder(16).build().unwrap();
+    /// let frames = wide.transition_to(&narrow, 4);
+    /// assert_eq!(frames.len(), 5);
+    /// assert_eq!(frames[0], wide.sizes());
+    /// assert_eq!(frames[2], vec![19, 4]);
+    /// assert_eq!(frames[4], narrow.sizes());
+    /// ```
+    pub fn transition_to(&self, target: &Container<C>, steps: usize) -> Vec<Vec<usize>> {
+        let from = self.sizes();
+        let to = target.sizes();
+        (0..=steps)
+            .map(|step| {
+                to.iter()
+                    .enumerate()
+                    .map(|(i, &end)| {
+                        let start = from.get(i).copied().unwrap_or(0) as f64;
+                        let t = if steps == 0 {
+                            1.0
+                        } else {
+                            step as f64 / steps as f64
+                        };
+                        round(start + (end as f64 - start) * t)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+    /// Return a snapshot of the layout, without the contents
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container, LayoutResult};
+    ///
+    /// let container = Container::builder_in(20)
+    ///     .with_margin_between(1)
+    ///     .with(Child::new("a").with_size(8))
+    ///     .with(Child::new("b").with_size(30).optional())
+    ///     .with(Child::new("c"))
+    ///     .build()
+    ///     .unwrap();
+    /// let result = container.result();
+    /// assert_eq!(
+    ///     result,
+    ///     LayoutResult {
+    ///         available: 20,
+    ///         margin: 1,
+    ///         sizes: vec![Some(8), None, Some(11)],
+    ///         offsets: vec![0, 8, 9],
+    ///     },
+    /// );
+    /// ```
+    pub fn result(&self) -> LayoutResult {
+        LayoutResult {
+            available: self.available,
+            margin: self.settings.margin_between,
+            sizes: self.sized(),
+            offsets: self.offsets(),
+        }
+    }
+    /// Return the offsets of the children, in the order they were added,
+    /// taking margins into account.
+    ///
+    /// A non-included child gets the offset it would have if it had a zero size.
+    pub fn offsets(&self) -> Vec<usize> {
+        let mut offsets = Vec::with_capacity(self.children.len());
+        let mut offset = self.settings.margin_around;
+        let mut previous = None;
+        for child in &self.children {
+            let Some(size) = child.size else {
+                offsets.push(offset);
+                continue;
+            };
+            if child.constraints.overlay {
+                offsets.push(self.settings.margin_around);
+                continue;
+            }
+            if let Some(previous) = previous {
+                offset += self.settings.margin(previous, &child.constraints);
+            }
+            previous = Some(&child.constraints);
+            offsets.push(offset);
+            offset += size;
+        }
+        offsets
+    }
+    /// Return the indices of the non-included children, with the reason
+    /// why they were dropped
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container, DropReason};
+    ///
+    /// let container = Container::builder_in(40)
+    ///     .with(Child::new("name").with_size(10))
+    ///     .with(Child::new("comments").with_size(20).optional_with_priority(10))
+    ///     .with(Child::new("vendor").with_size(15).optional_with_priority(9))
+    ///     .with(Child::new("notes").with_size(50).optional_with_priority(2))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![10, 20, 0, 0]);
+    /// assert_eq!(
+    ///     container.drop_reasons(),
+    ///     vec![(2, DropReason::LowerPriority), (3, DropReason::NoSpace)],
+    /// );
+    /// ```
+    pub fn drop_reasons(&self) -> Vec<(usize, DropReason)> {
+        self.children
+            .iter()
+            .enumerate()
+            .filter_map(|(i, child)| child.drop_reason.map(|reason| (i, reason)))
+            .collect()
+    }
+    /// Return the `(offset, size)` of the region after the last included
+    /// child, up to the end of the available space, if the container was
+    /// built with a trailing fill
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(40)
+    ///     .with_margin_between(1)
+    ///     .with_trailing_fill(true)
+    ///     .with(Child::new("a").with_size(10))
+    ///     .with(Child::new("b").with_size(9))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![10, 9]);
+    /// assert_eq!(container.trailing_fill(), Some((20, 20)));
+    /// ```
+    pub fn trailing_fill(&self) -> Option<(usize, usize)> {
+        if !self.settings.trailing_fill {
+            return None;
+        }
+        let end = self.used() - self.settings.margin_around;
+        Some((
+            end,
+            self.available
+                .saturating_sub(end + self.settings.margin_around),
+        ))
+    }
+    /// Return the size used by the included children and the margins
+    /// between and around them
+    pub fn used(&self) -> usize {
+        let around = self.settings.margin_around;
+        let end = self
+            .offsets()
+            .iter()
+            .zip(&self.children)
+            .filter(|(_, child)| !child.constraints.overlay)
+            .filter_map(|(offset, child)| child.size.map(|size| offset + size))
+            .last()
+            .unwrap_or(around);
+        end + around
+    }
+    /// Return the part of the available size which isn't used by
+    /// the children and the margins between them
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(50)
+    ///     .with_margin_between(2)
+    ///     .with(Child::new("a").with_max(10))
+    ///     .with(Child::new("b").with_size(7))
+    ///     .with(Child::new("c").with_max(12))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![10, 7, 12]);
+    /// assert_eq!(container.used(), 33);
+    /// assert_eq!(container.leftover(), 17);
+    /// assert_eq!(container.used() + container.leftover(), 50);
+    /// ```
+    pub fn leftover(&self) -> usize {
+        self.available.saturating_sub(self.used())
+    }
+    /// Tell whether some of the available size isn't used, for example
+    /// because all children reached their max
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(30)
+    ///     .with_margin_between(1)
+    ///     .with(Child::new("a").with_size(8))
+    ///     .with(Child::new("b").with_size(10))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![8, 10]);
+    /// assert_eq!(container.leftover(), 11);
+    /// assert!(container.has_unused_space());
+    /// ```
+    pub fn has_unused_space(&self) -> bool {
+        self.leftover() > 0
+    }
+    /// Return by how much the included child at `index` can grow in the
+    /// given direction, taking cells from the nearest included child on
+    /// that side without making it smaller than its min, nor growing
+    /// over its own max (for example to bound the drag of a divider).
+    ///
+    /// At the end, without a child after, the leftover can be taken.
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container, Edge};
+    ///
+    /// let container = Container::builder_in(41)
+    ///     .with_margin_between(1)
+    ///     .with(Child::new("left").with_min(5))
+    ///     .with(Child::new("right").with_min(12))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![17, 23]);
+    /// assert_eq!(container.max_resize(0, Edge::End), 11);
+    /// assert_eq!(container.max_resize(1, Edge::Start), 12);
+    /// assert_eq!(container.max_resize(0, Edge::Start), 0);
+    ///
+    /// // the bounds are the ones the layout used
+    /// let container = Container::builder_in(40)
+    ///     .with(Child::new("left").with_min(5))
+    ///     .with(Child::new("right").with_fraction(0.5))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![13, 27]);
+    /// assert_eq!(container.max_resize(0, Edge::End), 7);
+    /// let container = Container::builder_in(40)
+    ///     .with_scale(2.0)
+    ///     .with(Child::new("left").with_min(5))
+    ///     .with(Child::new("right").with_min(12))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![13, 27]);
+    /// assert_eq!(container.max_resize(0, Edge::End), 3);
+    /// ```
+    pub fn max_resize(&self, index: usize, direction: Edge) -> usize {
+        let Some(child) = self.children.get(index) else {
+            return 0;
+        };
+        let Some(size) = child.size else {
+            return 0;
+        };
+        let neighbor = match direction {
+            Edge::Start => self.children[..index]
+                .iter()
+                .rev()
+                .find(|c| c.size.is_some()),
+            Edge::End => self.children[index + 1..].iter().find(|c| c.size.is_some()),
+        };
+        let slack = match (neighbor, direction) {
+            (Some(neighbor), _) => neighbor
+                .size
+                .unwrap_or(0)
+                .saturating_sub(neighbor.resolved.min),
+            (None, Edge::Start) => 0,
+            (None, Edge::End) => self.leftover(),
+        };
+        match child.resolved.max {
+            Some(max) => slack.min(max.saturating_sub(size)),
+            None => slack,
+        }
+    }
+    /// Apply a function to the size of every included child.
+    ///
+    /// Nothing is checked: it's the caller's responsibility to keep the
+    /// sizes consistent (the used size may exceed the available one).
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let mut container = Container::builder_in(30)
+    ///     .with(Child::new("a").with_size(10))
+    ///     .with(Child::new("b").with_size(40).optional())
+    ///     .with(Child::new("c"))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![10, 0, 20]);
+    /// container.map_sizes(|size| size - 1);
+    /// assert_eq!(container.sized(), vec![Some(9), None, Some(19)]);
+    /// ```
+    pub fn map_sizes<F>(&mut self, mut f: F)
+    where
+        F: FnMut(usize) -> usize,
+    {
+        for child in &mut self.children {
+            child.size = child.size.map(&mut f);
+        }
+    }
+    /// Make the included children bigger, ignoring their max, so that
+    /// they use exactly `total`, the difference being shared evenly
+    /// (the first children getting the remaining cells). Overlay children
+    /// aren't changed.
+    ///
+    /// Does nothing if the children already use `total` or more.
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let mut container = Container::builder_in(30)
+    ///     .with_margin_between(1)
+    ///     .with(Child::new("a").with_size(5))
+    ///     .with(Child::new("b").with_size(6))
+    ///     .with(Child::new("c").with_size(7))
+    ///     .with(Child::new("d").with_size(20).optional())
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.used(), 20);
+    /// container.fill_to(container.available());
+    /// assert_eq!(container.sizes(), vec![9, 9, 10, 0]);
+    /// assert_eq!(container.used(), 30);
+    ///
+    /// let mut container = Container::builder_in(30)
+    ///     .with_no_stretch(true)
+    ///     .with(Child::new("a").with_size(5))
+    ///     .with(Child::new("badge").clamp(2, 3).overlay(true))
+    ///     .with(Child::new("b").with_size(5))
+    ///     .build()
+    ///     .unwrap();
+    /// container.fill_to(30);
+    /// assert_eq!(container.sizes(), vec![15, 3, 15]);
+    /// assert_eq!(container.used(), 30);
+    /// ```
+    pub fn fill_to(&mut self, total: usize) {
+        let used = self.used();
+        let count = self
+            .children
+            .iter()
+            .filter(|c| c.size.is_some() && !c.constraints.overlay)
+            .count();
+        if used >= total || count == 0 {
+            return;
+        }
+        let share = (total - used) / count;
+        let mut remainder = (total - used) % count;
+        for child in &mut self.children {
+            let Some(size) = child.size else {
+                continue;
+            };
+            if child.constraints.overlay {
+                continue;
+            }
+            let mut growth = share;
+            if remainder > 0 {
+                growth += 1;
+                remainder -= 1;
+            }
+            child.size = Some(size + growth);
+        }
+        self.available = self.available.max(total);
+    }
+    /// Return the ratio of the available size which is used, between
+    /// 0.0 and 1.0 (0.0 when nothing is available)
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let packed = Container::builder_in(20)
+    ///     .with_margin_between(1)
+    ///     .with(Child::new("a").with_min(5))
+    ///     .with(Child::new("b").with_min(5))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(packed.fill_ratio(), 1.0);
+    /// let sparse = Container::builder_in(20)
+    ///     .with(Child::new("a").with_size(5))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(sparse.fill_ratio(), 0.25);
+    /// ```
+    pub fn fill_ratio(&self) -> f64 {
+        if self.available == 0 {
+            return 0.0;
+        }
+        (self.used() as f64 / self.available as f64).min(1.0)
+    }
+    /// Return the indices of the children from the most important to the
+    /// least important: required children first, then the other ones by
+    /// decreasing priority (or value density, depending on the strategy).
+    /// This is the order the allocation considered them, which puts first
+    /// the children of the best set with `OptionalStrategy::MinDroppedPriority`
+    /// and the members of a bundle with the first of them.
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container, OptionalStrategy};
+    ///
+    /// let container = Container::builder_in(50)
+    ///     .with_margin_between(1)
+    ///     .with(Child::new("name").clamp(5, 10))
+    ///     .with(Child::new("price").with_size(8).optional_with_priority(7))
+    ///     .with(Child::new("quantity").with_size(8).optional())
+    ///     .with(Child::new("total").with_size(8))
+    ///     .with(Child::new("comments").with_min(10).with_grow(2.0))
+    ///     .with(Child::new("vendor").with_size(60).optional_with_priority(9))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.by_priority(), vec![0, 3, 4, 5, 1, 2]);
+    ///
+    /// let container = Container::builder_in(20)
+    ///     .with_optional_strategy(OptionalStrategy::MinDroppedPriority)
+    ///     .with(Child::new("a").with_size(12).optional_with_priority(5))
+    ///     .with(Child::new("b").with_size(9).optional_with_priority(4))
+    ///     .with(Child::new("c").with_size(9).optional_with_priority(4))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.by_priority(), vec![1, 2, 0]);
+    ///
+    /// let container = Container::builder_in(20)
+    ///     .with(Child::new("a").with_size(5).optional_with_priority(3))
+    ///     .with(Child::new("b").with_size(5).optional_with_priority(1).in_bundle(1))
+    ///     .with(Child::new("c").with_size(5).optional_with_priority(2))
+    ///     .with(Child::new("d").with_size(5).optional_with_priority(5).in_bundle(1))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.by_priority(), vec![3, 1, 0, 2]);
+    /// ```
+    pub fn by_priority(&self) -> Vec<usize> {
+        let mut order = priority_order(&self.children, &self.settings);
+        // stable, so the children which weren't considered (when there
+        // was no space) keep the priority order
+        order.sort_by_key(|&i| self.children[i].rank.unwrap_or(usize::MAX));
+        order
+    }
+    /// Panic with a readable message if the sizes of the children
+    /// aren't the expected ones
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(20)
+    ///     .with(Child::new("a").with_size(8))
+    ///     .with(Child::new("b"))
+    ///     .build()
+    ///     .unwrap();
+    /// container.assert_sizes(&[8, 12]);
+    /// ```
+    ///
+    /// ```should_panic
+    /// # use flex_grow::{Child, Container};
+    /// # let container = Container::builder_in(20)
+    /// #     .with(Child::new("a").with_size(8))
+    /// #     .with(Child::new("b"))
+    /// #     .build()
+    /// #     .unwrap();
+    /// container.assert_sizes(&[8, 10]);
+    /// ```
+    #[cfg(feature = "test-util")]
+    #[track_caller]
+    pub fn assert_sizes(&self, expected: &[usize]) {
+        let actual = self.sizes();
+        if actual == expected {
+            return;
+        }
+        let mut message = format!(
+            "container sizes don't match\nexpected: {:?}\n  actual: {:?}",
+            expected, actual,
+        );
+        if actual.len() != expected.len() {
+            message.push_str(&format!(
+                "\nexpected {} children, got {}",
+                expected.len(),
+                actual.len(),
+            ));
+        }
+        for (i, (e, a)) in expected.iter().zip(&actual).enumerate() {
+            if e != a {
+                message.push_str(&format!("\nchild {}: expected {}, got {}", i, e, a));
+            }
+        }
+        panic!("{}", message);
+    }
+    /// Return the child at the given index, or none if out of range
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(10)
+    ///     .with(Child::new("a"))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.get(0).map(|c| *c.content()), Some("a"));
+    /// assert!(container.get(1).is_none());
+    /// ```
+    pub fn get(&self, index: usize) -> Option<&Child<C>> {
+        self.children.get(index)
+    }
+    /// Return a mutable reference to the child at the given index,
+    /// or none if out of range
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut Child<C>> {
+        self.children.get_mut(index)
+    }
+    /// Return the size of the first child whose content is the given key,
+    /// or none if there's no such child or if it isn't included
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(40)
+    ///     .with_margin_between(1)
+    ///     .with(Child::new("name").with_min(10))
+    ///     .with(Child::new("comments").with_size(50).optional())
+    ///     .with(Child::new("total").with_min(8).with_grow(2.0))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.size_of("total"), Some(container.sizes()[2]));
+    /// assert_eq!(container.size_of("comments"), None);
+    /// assert_eq!(container.size_of("price"), None);
+    /// ```
+    pub fn size_of<Q>(&self, key: &Q) -> Option<usize>
+    where
+        C: Borrow<Q>,
+        Q: PartialEq + ?Sized,
+    {
+        self.children
+            .iter()
+            .find(|child| child.content.borrow() == key)
+            .and_then(|child| child.size)
+    }
     pub fn children(&self) -> &[Child<C>] {
         &self.children
     }
+    /// Iterate over the included children, in the order they were added,
+    /// giving their content and size
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(20)
+    ///     .with(Child::new("a").with_size(8))
+    ///     .with(Child::new("b").with_size(30).optional())
+    ///     .with(Child::new("c").with_min(4))
+    ///     .build()
+    ///     .unwrap();
+    /// let visible: Vec<_> = container.visible().collect();
+    /// assert_eq!(visible, vec![(&"a", 8), (&"c", 12)]);
+    /// ```
+    pub fn visible(&self) -> impl Iterator<Item = (&C, usize)> {
+        self.children
+            .iter()
+            .filter_map(|child| child.size.map(|size| (&child.content, size)))
+    }
+    /// Iterate over the non-included children, in the order they were added
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(30)
+    ///     .with(Child::new("a").with_size(10))
+    ///     .with(Child::new("b").with_size(15).optional_with_priority(2))
+    ///     .with(Child::new("c").with_size(8).optional_with_priority(9))
+    ///     .with(Child::new("d").with_size(13).optional_with_priority(1))
+    ///     .build()
+    ///     .unwrap();
+    /// let dropped: Vec<_> = container.dropped().map(|c| *c.content()).collect();
+    /// assert_eq!(dropped, vec!["b", "d"]);
+    /// assert!(container.children[2].is_included());
+    /// ```
+    pub fn dropped(&self) -> impl Iterator<Item = &Child<C>> {
+        self.children.iter().filter(|child| !child.is_included())
+    }
+    /// Iterate over the included children, in the order they were added,
+    /// with the margin before each of them (0 for the first one and
+    /// for overlay children)
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container};
+    ///
+    /// let container = Container::builder_in(30)
+    ///     .with_margin_between(2)
+    ///     .with(Child::new("a").with_size(8))
+    ///     .with(Child::new("b").with_size(40).optional())
+    ///     .with(Child::new("c").with_size(8))
+    ///     .with(Child::new("d"))
+    ///     .build()
+    ///     .unwrap();
+    /// let included: Vec<_> = container
+    ///     .included()
+    ///     .map(|(child, margin)| (*child.content(), margin))
+    ///     .collect();
+    /// assert_eq!(included, vec![("a", 0), ("c", 2), ("d", 2)]);
+    /// ```
+    pub fn included(&self) -> impl Iterator<Item = (&Child<C>, usize)> {
+        let mut previous: Option<&ChildConstraints> = None;
+        self.children
+            .iter()
+            .filter(|child| child.is_included())
+            .map(move |child| {
+                if child.constraints.overlay {
+                    return (child, 0);
+                }
+                let margin = previous.map_or(0, |previous| {
+                    self.settings.margin(previous, &child.constraints)
+                });
+                previous = Some(&child.constraints);
+                (child, margin)
+            })
+    }
     pub fn to_children(self) -> Vec<Child<C>> {
         self.children
     }
@@ -0,0 +1,65 @@
+/// The order in which the optional children are considered for inclusion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum OptionalStrategy {
+    /// By decreasing priority, then in declaration order
+    #[default]
+    Priority,
+    /// By decreasing value per cell of min size (see
+    /// `Child::optional_with_value`), then in declaration order
+    ValueDensity,
+    /// The set of optional children with the biggest sum of priorities
+    /// which fits first, then the other ones by decreasing priority
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container, OptionalStrategy};
+    ///
+    /// let builder = || {
+    ///     Container::builder_in(20)
+    ///         .with(Child::new("a").with_size(12).optional_with_priority(5))
+    ///         .with(Child::new("b").with_size(9).optional_with_priority(4))
+    ///         .with(Child::new("c").with_size(9).optional_with_priority(4))
+    /// };
+    /// let greedy = builder().build().unwrap();
+    /// assert_eq!(greedy.sizes(), vec![12, 0, 0]);
+    /// let optimal = builder()
+    ///     .with_optional_strategy(OptionalStrategy::MinDroppedPriority)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(optimal.sizes(), vec![0, 9, 9]);
+    /// ```
+    ///
+    /// The margins are taken into account, and big sizes don't make
+    /// the resolution too costly:
+    ///
+    /// ```
+    /// use flex_grow::{Child, Container, OptionalStrategy};
+    ///
+    /// let container = Container::builder_in(20)
+    ///     .with_optional_strategy(OptionalStrategy::MinDroppedPriority)
+    ///     .with(Child::new("r").with_size(2))
+    ///     .with(Child::new("a").with_size(8).optional_with_priority(5).with_margin_after(4))
+    ///     .with(Child::new("b").with_size(8).optional_with_priority(4))
+    ///     .with(Child::new("c").with_size(8).optional_with_priority(4))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![2, 0, 8, 8]);
+    ///
+    /// let big = (1usize << 39) + 1;
+    /// let container = Container::builder_in(1 << 40)
+    ///     .with_optional_strategy(OptionalStrategy::MinDroppedPriority)
+    ///     .with(Child::new("a").with_size(big).optional_with_priority(2))
+    ///     .with(Child::new("b").with_size(big).optional_with_priority(1))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(container.sizes(), vec![big, 0]);
+    ///
+    /// let mut builder = Container::builder_in(2_000_000)
+    ///     .with_optional_strategy(OptionalStrategy::MinDroppedPriority);
+    /// for i in 0..200 {
+    ///     builder.add(Child::new(i).with_size(20_000 + i).optional_with_priority(i % 7));
+    /// }
+    /// assert!(builder.build().is_ok());
+    /// ```
+    MinDroppedPriority,
+}
@@ -0,0 +1,13 @@
+/// How a child is served when the cells left by the rounding
+/// of the shares are distributed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Rounding {
+    /// The child gets the remaining cells before the other ones
+    Up,
+    /// The child gets the remaining cells after the `Up` ones
+    #[default]
+    Nearest,
+    /// The child gets the remaining cells only when the other ones can't
+    Down,
+}
@@ -0,0 +1,20 @@
+//! A `no_std` crate using flex-grow, built by the `no_std` test to
+//! check the crate compiles without the standard library
+#![no_std]
+
+extern crate alloc;
+
+use {
+    alloc::vec::Vec,
+    flex_grow::{Child, Container, Error},
+};
+
+pub fn sizes(available: usize) -> Result<Vec<usize>, Error> {
+    let container = Container::builder_in(available)
+        .with_margin_between(1)
+        .with(Child::new("name").clamp(5, 10))
+        .with(Child::new("price").with_size(8).optional())
+        .with(Child::new("comments").with_min(10))
+        .build()?;
+    Ok(container.sizes())
+}
@@ -0,0 +1,17 @@
+use std::{path::Path, process::Command};
+
+/// Build a `no_std` crate depending on flex-grow without its default
+/// features, to check the crate doesn't need std
+#[test]
+fn builds_without_std() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let status = Command::new(env!("CARGO"))
+        .arg("build")
+        .arg("--manifest-path")
+        .arg(root.join("tests/no-std-check/Cargo.toml"))
+        .arg("--target-dir")
+        .arg(root.join("target/no-std-check"))
+        .status()
+        .expect("cargo should run");
+    assert!(status.success());
+}
@@ -0,0 +1,43 @@
+#![cfg(feature = "serde")]
+
+use flex_grow::*;
+
+#[test]
+fn child_constraints_round_trip() {
+    let constraints = Child::new("price")
+        .clamp(4, 12)
+        .optional_with_priority(7)
+        .with_grow(2.5)
+        .with_margin_after(3)
+        .with_rounding(Rounding::Up)
+        .constraints();
+    let json = serde_json::to_string(&constraints).unwrap();
+    let back: ChildConstraints = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, constraints);
+    let squeezable = Optionality::Squeezable {
+        priority: 3,
+        floor: 2,
+    };
+    let json = serde_json::to_string(&squeezable).unwrap();
+    assert_eq!(json, r#"{"Squeezable":{"priority":3,"floor":2}}"#);
+    assert_eq!(serde_json::from_str::<Optionality>(&json).unwrap(), squeezable);
+}
+
+#[test]
+fn layout_result_round_trip() {
+    let container = Container::builder_in(20)
+        .with_margin_between(1)
+        .with(Child::new("a").with_size(8))
+        .with(Child::new("b").with_size(30).optional())
+        .with(Child::new("c"))
+        .build()
+        .unwrap();
+    let result = container.result();
+    let json = serde_json::to_string(&result).unwrap();
+    assert_eq!(
+        json,
+        r#"{"available":20,"margin":1,"sizes":[8,null,11],"offsets":[0,8,9]}"#,
+    );
+    let back: LayoutResult = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, result);
+}
@@ -0,0 +1,29 @@
+#![cfg(feature = "test-util")]
+
+use flex_grow::*;
+
+fn container() -> Container<&'static str> {
+    Container::builder_in(20)
+        .with(Child::new("a").with_size(8))
+        .with(Child::new("b").with_size(5).optional())
+        .with(Child::new("c"))
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn assert_sizes_accepts_the_actual_sizes() {
+    container().assert_sizes(&[8, 5, 7]);
+}
+
+#[test]
+#[should_panic(expected = "child 2: expected 6, got 7")]
+fn assert_sizes_reports_the_wrong_sizes() {
+    container().assert_sizes(&[8, 5, 6]);
+}
+
+#[test]
+#[should_panic(expected = "expected 2 children, got 3")]
+fn assert_sizes_reports_the_wrong_count() {
+    container().assert_sizes(&[8, 5]);
+}